@@ -1,8 +1,20 @@
 //! Editor with your game connected to it as a plugin.
+mod terrain_preset_editor;
+
+use std::rc::Rc;
+use fyrox::core::futures::executor::block_on;
 use fyrox::event_loop::EventLoop;
 use fyroxed_base::{Editor, StartupData};
+use fyroxed_base::inspector::editors::resource::ResourceFieldPropertyEditorDefinition;
 use battered_battery_bundles::GameConstructor;
+use battered_battery_bundles::collectible::{CollectibleEffect, CollectiblePersistence};
+use battered_battery_bundles::moving_platform::PlatformMode;
 use battered_battery_bundles::player_controller::MoveDirection;
+use battered_battery_bundles::reverse_direction::{ReverseAction, ReverseCondition, ReverseTriggerMode};
+use battered_battery_bundles::terrain_effect::TerrainOverrides;
+use battered_battery_bundles::terrain_preset::TerrainPreset;
+use battered_battery_bundles::trigger::{TriggerAction, TriggerActionKind, TriggerEvent};
+use crate::terrain_preset_editor::TerrainPresetEditor;
 
 fn main() {
     let event_loop = EventLoop::new();
@@ -14,9 +26,26 @@ fn main() {
         }),
     );
 
+    GameConstructor::register_resources(&editor.engine.resource_manager);
+
     let editors = &editor.inspector.property_editors;
     editors.register_inheritable_enum::<MoveDirection, _>();
+    editors.register_inheritable_enum::<CollectibleEffect, _>();
+    editors.register_inheritable_enum::<CollectiblePersistence, _>();
+    editors.register_inheritable_enum::<ReverseTriggerMode, _>();
+    editors.register_inheritable_enum::<ReverseCondition, _>();
+    editors.register_inheritable_enum::<ReverseAction, _>();
+    editors.register_inheritable_enum::<PlatformMode, _>();
+    editors.register_inheritable_enum::<TriggerEvent, _>();
+    editors.register_inheritable_enum::<TriggerActionKind, _>();
+    editors.register_inheritable_inspectable::<TerrainOverrides>();
+    editors.register_inheritable_inspectable::<TriggerAction>();
+    editors.register_inheritable_vec_collection::<TriggerAction>();
+    editors.insert(ResourceFieldPropertyEditorDefinition::<TerrainPreset>::new(
+        Rc::new(|resource_manager, path| block_on(resource_manager.request::<TerrainPreset, _>(path))),
+    ));
 
     editor.add_game_plugin(GameConstructor);
+    editor.add_editor_plugin(TerrainPresetEditor::default());
     editor.run(event_loop)
 }
@@ -0,0 +1,162 @@
+//! Inspector for the terrain preset of the selected `TerrainEffect`.
+use std::path::PathBuf;
+use std::rc::Rc;
+use fyrox::asset::Resource;
+use fyrox::core::log::Log;
+use fyrox::core::pool::Handle;
+use fyrox::gui::button::{ButtonBuilder, ButtonMessage};
+use fyrox::gui::grid::{Column, GridBuilder, Row};
+use fyrox::gui::inspector::{InspectorBuilder, InspectorContext, InspectorMessage, PropertyAction};
+use fyrox::gui::message::{MessageDirection, UiMessage};
+use fyrox::gui::scroll_viewer::ScrollViewerBuilder;
+use fyrox::gui::stack_panel::StackPanelBuilder;
+use fyrox::gui::widget::WidgetBuilder;
+use fyrox::gui::window::{WindowBuilder, WindowMessage, WindowTitle};
+use fyrox::gui::{HorizontalAlignment, Orientation, Thickness, UiNode};
+use fyroxed_base::inspector::editors::make_property_editors_container;
+use fyroxed_base::plugin::EditorPlugin;
+use fyroxed_base::scene::Selection;
+use fyroxed_base::{Editor, MSG_SYNC_FLAG};
+use battered_battery_bundles::terrain_effect::TerrainEffect;
+use battered_battery_bundles::terrain_preset::TerrainPreset;
+
+/// Opens while a terrain zone with a preset is selected. Apply writes the values to the `.terrain`
+/// file and reloads it, so every zone sharing the preset changes with it.
+#[derive(Default)]
+pub struct TerrainPresetEditor {
+    window: Handle<UiNode>,
+    inspector: Handle<UiNode>,
+    apply: Handle<UiNode>,
+    revert: Handle<UiNode>,
+    path: Option<PathBuf>,
+    preset: TerrainPreset,
+}
+
+impl TerrainPresetEditor {
+    fn selected_preset(editor: &Editor) -> Option<Resource<TerrainPreset>> {
+        let editor_scene = editor.scenes.current_editor_scene_ref()?;
+        let nodes = match &editor_scene.selection {
+            Selection::Graph(selection) => &selection.nodes,
+            _ => return None,
+        };
+        let graph = &editor.engine.scenes[editor_scene.scene].graph;
+        nodes
+            .first()
+            .and_then(|node| graph.try_get(*node))
+            .and_then(|node| node.try_get_script::<TerrainEffect>())
+            .and_then(|effect| effect.preset.clone())
+            .filter(|preset| preset.is_ok())
+    }
+
+    fn sync(&self, editor: &mut Editor) {
+        let ui = &mut editor.engine.user_interface;
+        let context = InspectorContext::from_object(
+            &self.preset,
+            &mut ui.build_ctx(),
+            Rc::new(make_property_editors_container(editor.message_sender.clone())),
+            None,
+            MSG_SYNC_FLAG,
+            0,
+            true,
+            Default::default(),
+        );
+        ui.send_message(InspectorMessage::context(self.inspector, MessageDirection::ToWidget, context));
+    }
+
+    fn save(&self, editor: &mut Editor) {
+        let path = match self.path.as_ref() {
+            Some(path) => path,
+            None => return,
+        };
+        let written = self
+            .preset
+            .to_ron()
+            .map_err(|err| err.to_string())
+            .and_then(|text| std::fs::write(path, text).map_err(|err| err.to_string()));
+        match written {
+            Ok(()) => {
+                let resource = editor.engine.resource_manager.request::<TerrainPreset, _>(path);
+                editor.engine.resource_manager.state().reload_resource(resource.into_untyped());
+            }
+            Err(err) => Log::err(format!("Unable to save terrain preset {}: {}", path.display(), err)),
+        }
+    }
+}
+
+impl EditorPlugin for TerrainPresetEditor {
+    fn on_start(&mut self, editor: &mut Editor) {
+        let ctx = &mut editor.engine.user_interface.build_ctx();
+        let buttons = StackPanelBuilder::new(
+            WidgetBuilder::new()
+                .on_row(1)
+                .with_horizontal_alignment(HorizontalAlignment::Right)
+                .with_child({
+                    self.apply = ButtonBuilder::new(WidgetBuilder::new().with_width(100.0).with_margin(Thickness::uniform(1.0)))
+                        .with_text("Apply")
+                        .build(ctx);
+                    self.apply
+                })
+                .with_child({
+                    self.revert = ButtonBuilder::new(WidgetBuilder::new().with_width(100.0).with_margin(Thickness::uniform(1.0)))
+                        .with_text("Revert")
+                        .build(ctx);
+                    self.revert
+                }),
+        )
+        .with_orientation(Orientation::Horizontal)
+        .build(ctx);
+        self.inspector = InspectorBuilder::new(WidgetBuilder::new()).build(ctx);
+        let content = GridBuilder::new(
+            WidgetBuilder::new()
+                .with_child(ScrollViewerBuilder::new(WidgetBuilder::new()).with_content(self.inspector).build(ctx))
+                .with_child(buttons),
+        )
+        .add_row(Row::stretch())
+        .add_row(Row::strict(25.0))
+        .add_column(Column::stretch())
+        .build(ctx);
+        self.window = WindowBuilder::new(WidgetBuilder::new().with_width(320.0).with_height(360.0))
+            .with_title(WindowTitle::text("Terrain Preset"))
+            .with_content(content)
+            .open(false)
+            .build(ctx);
+    }
+
+    fn on_ui_message(&mut self, message: &mut UiMessage, editor: &mut Editor) {
+        if let Some(InspectorMessage::PropertyChanged(property_changed)) = message.data() {
+            if message.destination() == self.inspector {
+                PropertyAction::from_field_kind(&property_changed.value).apply(
+                    &property_changed.path(),
+                    &mut self.preset,
+                    &mut |result| Log::verify(result),
+                );
+            }
+        } else if let Some(ButtonMessage::Click) = message.data() {
+            if message.destination() == self.apply {
+                self.save(editor);
+            } else if message.destination() == self.revert {
+                if let Some(preset) = Self::selected_preset(editor) {
+                    self.preset = preset.data_ref().clone();
+                    self.sync(editor);
+                }
+            }
+        }
+    }
+
+    fn on_update(&mut self, editor: &mut Editor) {
+        let selected = Self::selected_preset(editor);
+        let path = selected.as_ref().map(|preset| preset.path());
+        if path == self.path {
+            return;
+        }
+        self.path = path;
+        match selected {
+            Some(preset) => {
+                self.preset = preset.data_ref().clone();
+                self.sync(editor);
+                editor.engine.user_interface.send_message(WindowMessage::open(self.window, MessageDirection::ToWidget, false));
+            }
+            None => editor.engine.user_interface.send_message(WindowMessage::close(self.window, MessageDirection::ToWidget)),
+        }
+    }
+}
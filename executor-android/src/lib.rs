@@ -12,6 +12,7 @@ fn android_main(app: fyrox::platform::android::activity::AndroidApp) {
         .expect("ANDROID_APP cannot be set twice.");
     let event_loop = EventLoopBuilder::new().with_android_app(app).build();
     let mut executor = Executor::from_params(event_loop, Default::default());
+    GameConstructor::register_resources(&executor.resource_manager);
     executor.add_plugin_constructor(GameConstructor);
     executor.run()
 }
\ No newline at end of file
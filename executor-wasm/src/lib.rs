@@ -40,6 +40,7 @@ pub fn set_panic_hook() {
 pub fn main() {
     set_panic_hook();
     let mut executor = Executor::new();
+    GameConstructor::register_resources(&executor.resource_manager);
     executor.add_plugin_constructor(GameConstructor);
     executor.run()
 }
\ No newline at end of file
@@ -1,9 +1,18 @@
 //! Executor with your game connected to it as a plugin.
+use std::time::Duration;
+use fyrox::core::log::Log;
 use fyrox::engine::executor::Executor;
+use fyrox::core::watcher::FileSystemWatcher;
 use battered_battery_bundles::GameConstructor;
 
 fn main() {
     let mut executor = Executor::new();
+    GameConstructor::register_resources(&executor.resource_manager);
+    // Presets and other data files are picked up while the game runs, the editor has its own watcher.
+    match FileSystemWatcher::new("data", Duration::from_secs(1)) {
+        Ok(watcher) => executor.resource_manager.state().set_watcher(Some(watcher)),
+        Err(err) => Log::warn(format!("Unable to watch data folder, hot reload disabled: {}", err)),
+    }
     executor.add_plugin_constructor(GameConstructor);
     executor.run()
-}
\ No newline at end of file
+}
@@ -0,0 +1,100 @@
+//! Compares every zone scanning its own intersections against one shared contact pass.
+//!
+//! Run with `cargo bench -p battered_battery_bundles --bench contact_service`.
+use std::time::{Duration, Instant};
+use fyrox::core::algebra::{Vector2, Vector3};
+use fyrox::core::pool::Handle;
+use fyrox::scene::base::BaseBuilder;
+use fyrox::scene::collider::{ColliderBuilder, ColliderShape};
+use fyrox::scene::node::Node;
+use fyrox::scene::rigidbody::{RigidBodyBuilder, RigidBodyType};
+use fyrox::scene::transform::TransformBuilder;
+use fyrox::scene::Scene;
+use fyrox::script::Script;
+use battered_battery_bundles::contacts::ContactService;
+use battered_battery_bundles::player_controller::PlayerController;
+use battered_battery_bundles::terrain_effect::TerrainEffect;
+use battered_battery_bundles::trigger::player_in_sensor;
+
+const FRAMES: u32 = 600;
+const SETTLE_FRAMES: u32 = 5;
+
+fn build_scene(zones: usize) -> (Scene, Vec<Handle<Node>>) {
+    let mut scene = Scene::new();
+    let graph = &mut scene.graph;
+
+    let player_collider = ColliderBuilder::new(BaseBuilder::new())
+        .with_shape(ColliderShape::ball(0.5))
+        .build(graph);
+    // Dynamic like the real player, rapier doesn't pair kinematic bodies with static sensors.
+    let player = RigidBodyBuilder::new(BaseBuilder::new()
+        .with_children(&[player_collider])
+        .with_script(Script::new(PlayerController::default())))
+        .with_gravity_scale(0.0)
+        .build(graph);
+    if let Some(controller) = graph[player].try_get_script_mut::<PlayerController>() {
+        controller.set_collider(player_collider);
+    }
+
+    // A row of zones like a long level, the player overlaps the first few.
+    let zone_colliders = (0..zones)
+        .map(|i| {
+            ColliderBuilder::new(BaseBuilder::new()
+                .with_local_transform(TransformBuilder::new()
+                    .with_local_position(Vector3::new(i as f32 * 0.75, 0.0, 0.0))
+                    .build())
+                .with_script(Script::new(TerrainEffect::default())))
+                .with_shape(ColliderShape::cuboid(0.5, 0.5, 0.5))
+                .with_sensor(true)
+                .build(graph)
+        })
+        .collect::<Vec<_>>();
+    RigidBodyBuilder::new(BaseBuilder::new().with_children(&zone_colliders))
+        .with_body_type(RigidBodyType::Static)
+        .build(graph);
+
+    // Intersections show up after the broad and narrow phase have both run.
+    for _ in 0..SETTLE_FRAMES {
+        scene.update(Vector2::new(1.0, 1.0), 1.0 / 60.0, Default::default());
+    }
+    (scene, zone_colliders)
+}
+
+fn time(mut frame: impl FnMut()) -> Duration {
+    let start = Instant::now();
+    for _ in 0..FRAMES {
+        frame();
+    }
+    start.elapsed() / FRAMES
+}
+
+fn main() {
+    for zones in [10, 100, 1000] {
+        let (scene, zone_colliders) = build_scene(zones);
+
+        // Both paths have to see the overlaps, otherwise they only time empty lists.
+        let mut service = ContactService::default();
+        service.update(&scene.graph);
+        let overlapped = zone_colliders.iter().filter(|z| service.player_at(**z).is_some()).count();
+        assert!(overlapped > 0, "contact service sees no overlaps");
+        assert_eq!(
+            zone_colliders.iter().filter(|z| player_in_sensor(**z, &scene.graph).is_some()).count(),
+            overlapped,
+        );
+
+        let per_zone = time(|| {
+            for zone in zone_colliders.iter() {
+                std::hint::black_box(player_in_sensor(*zone, &scene.graph));
+            }
+        });
+
+        let shared = time(|| {
+            service.update(&scene.graph);
+            for zone in zone_colliders.iter() {
+                std::hint::black_box(service.player_at(*zone));
+            }
+        });
+
+        println!("{:>5} zones: per-zone scan {:>10.2?}/frame, contact service {:>10.2?}/frame", zones, per_zone, shared);
+    }
+}
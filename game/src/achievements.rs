@@ -0,0 +1,195 @@
+//! Data-driven achievements. Definitions live in `data/achievements.ron` as conditions over
+//! gameplay events, progress is kept in the profile.
+use std::collections::BTreeMap;
+use fyrox::core::log::Log;
+use serde::{Deserialize, Serialize};
+use crate::difficulty::Difficulty;
+use crate::events::{GameplayEvent, GameplayObserver};
+
+/// Gameplay events without their data, for use in definitions.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventKind {
+    Damage,
+    Pickup,
+    Delivery,
+    Death,
+    Checkpoint,
+    DirectionChange,
+    Jump,
+    LevelCompleted,
+}
+
+impl EventKind {
+    pub fn of(event: &GameplayEvent) -> Self {
+        match event {
+            GameplayEvent::Damage { .. } => EventKind::Damage,
+            GameplayEvent::Pickup { .. } => EventKind::Pickup,
+            GameplayEvent::Delivery { .. } => EventKind::Delivery,
+            GameplayEvent::Death { .. } => EventKind::Death,
+            GameplayEvent::Checkpoint { .. } => EventKind::Checkpoint,
+            GameplayEvent::DirectionChange { .. } => EventKind::DirectionChange,
+            GameplayEvent::Jump { .. } => EventKind::Jump,
+            GameplayEvent::LevelCompleted => EventKind::LevelCompleted,
+        }
+    }
+}
+
+/// How much an event counts for, packages for pickups and deliveries and one for the rest.
+fn amount(event: &GameplayEvent) -> u32 {
+    match event {
+        GameplayEvent::Pickup { count, .. } | GameplayEvent::Delivery { count, .. } => *count as u32,
+        _ => 1,
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum AchievementCondition {
+    /// Adds up events over every run, e.g. jumps.
+    Total { event: EventKind, target: u32 },
+    /// Adds up events in a row, starting over whenever `reset_on` happens.
+    Streak { event: EventKind, reset_on: EventKind, target: u32 },
+    /// Completing a level, any level or difficulty when left out.
+    CompleteLevel {
+        #[serde(default)]
+        level: Option<String>,
+        #[serde(default)]
+        difficulty: Option<Difficulty>,
+    },
+}
+
+impl AchievementCondition {
+    pub fn target(&self) -> u32 {
+        match self {
+            AchievementCondition::Total { target, .. } | AchievementCondition::Streak { target, .. } => *target,
+            AchievementCondition::CompleteLevel { .. } => 1,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AchievementDefinition {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub condition: AchievementCondition,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct AchievementProgress {
+    pub progress: u32,
+    pub unlocked: bool,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct AchievementList {
+    pub achievements: Vec<AchievementDefinition>,
+}
+
+impl AchievementList {
+    pub fn from_ron(text: &str) -> Result<Self, ron::error::SpannedError> {
+        ron::from_str(text)
+    }
+
+    pub fn load(path: &str) -> Self {
+        match std::fs::read_to_string(path).map_err(|err| err.to_string())
+            .and_then(|text| Self::from_ron(&text).map_err(|err| err.to_string()))
+        {
+            Ok(list) => list,
+            Err(err) => {
+                Log::err(format!("Unable to load achievements {}: {}", path, err));
+                Self::default()
+            }
+        }
+    }
+}
+
+/// Evaluates the definitions against delivered events. The level and difficulty of the current
+/// run are set from outside since the events don't carry them.
+#[derive(Default, Debug, Clone)]
+pub struct AchievementTracker {
+    definitions: Vec<AchievementDefinition>,
+    progress: BTreeMap<String, AchievementProgress>,
+    level: String,
+    difficulty: Difficulty,
+    unlocked: Vec<String>,
+}
+
+impl AchievementTracker {
+    pub fn new(list: AchievementList, progress: BTreeMap<String, AchievementProgress>) -> Self {
+        Self {
+            definitions: list.achievements,
+            progress,
+            ..Default::default()
+        }
+    }
+
+    pub fn set_run(&mut self, level: &str, difficulty: Difficulty) {
+        self.level = level.to_string();
+        self.difficulty = difficulty;
+    }
+
+    /// A difficulty change in the middle of a run can only make it count as easier, so switching
+    /// to Hard before the last delivery doesn't clear a level on Hard.
+    pub fn change_difficulty(&mut self, difficulty: Difficulty) {
+        if difficulty.rank() < self.difficulty.rank() {
+            self.difficulty = difficulty;
+        }
+    }
+
+    pub fn definitions(&self) -> &[AchievementDefinition] {
+        &self.definitions
+    }
+
+    pub fn progress(&self) -> &BTreeMap<String, AchievementProgress> {
+        &self.progress
+    }
+
+    pub fn is_unlocked(&self, id: &str) -> bool {
+        self.progress.get(id).map_or(false, |p| p.unlocked)
+    }
+
+    /// Names of the achievements unlocked since the last call, for the HUD.
+    pub fn take_unlocked(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.unlocked)
+    }
+}
+
+impl GameplayObserver for AchievementTracker {
+    fn on_event(&mut self, event: &GameplayEvent) {
+        let kind = EventKind::of(event);
+        for definition in self.definitions.iter() {
+            let progress = self.progress.entry(definition.id.clone()).or_default();
+            if progress.unlocked {
+                continue;
+            }
+            match &definition.condition {
+                AchievementCondition::Total { event: counted, .. } => {
+                    if *counted == kind {
+                        progress.progress += amount(event);
+                    }
+                }
+                AchievementCondition::Streak { event: counted, reset_on, .. } => {
+                    if *reset_on == kind {
+                        progress.progress = 0;
+                    } else if *counted == kind {
+                        progress.progress += amount(event);
+                    }
+                }
+                AchievementCondition::CompleteLevel { level, difficulty } => {
+                    if kind == EventKind::LevelCompleted
+                        && level.as_ref().map_or(true, |level| *level == self.level)
+                        && difficulty.map_or(true, |difficulty| difficulty == self.difficulty)
+                    {
+                        progress.progress = 1;
+                    }
+                }
+            }
+            if progress.progress >= definition.condition.target() {
+                progress.unlocked = true;
+                self.unlocked.push(definition.name.clone());
+            }
+        }
+    }
+}
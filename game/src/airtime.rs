@@ -0,0 +1,133 @@
+use std::f32::consts::TAU;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LaunchKind {
+    Jump,
+    JumpPad,
+    /// Left the ground going up without jumping, e.g. off the end of a ramp.
+    Ramp,
+    /// Rolled off an edge.
+    Fall,
+    /// Thrown by a collision, e.g. with a vehicle.
+    Knockback,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Landing {
+    Upright { launch: LaunchKind, air_time: f32, flips: u32 },
+    Crash { launch: LaunchKind, air_time: f32 },
+}
+
+#[derive(Default, Debug, Clone)]
+pub struct AirtimeStats {
+    pub jumps: u32,
+    pub big_airs: u32,
+    pub flips: u32,
+    pub crashes: u32,
+    pub best_air_time: f32,
+    pub score: u32,
+}
+
+/// Settings for telling launches and landings apart.
+#[derive(Debug, Clone)]
+pub struct AirtimeRules {
+    /// Upward speed on take-off that counts as a ramp launch.
+    pub ramp_launch_speed: f32,
+    /// Seconds in the air that count as big air.
+    pub big_air_time: f32,
+    /// Cosine of the largest tilt that still counts as landing upright.
+    pub upright_cos: f32,
+}
+
+/// Follows the player between take-off and landing.
+#[derive(Default, Debug, Clone)]
+pub struct AirtimeTracker {
+    airborne: bool,
+    pending_launch: Option<LaunchKind>,
+    launch: Option<LaunchKind>,
+    air_time: f32,
+    rotation: f32,
+    pub stats: AirtimeStats,
+}
+
+impl AirtimeTracker {
+    /// Marks the next take-off as caused by a jump or jump pad rather than the terrain.
+    pub fn launch(&mut self, kind: LaunchKind) {
+        self.pending_launch = Some(kind);
+        if self.airborne {
+            self.launch = Some(kind);
+        }
+    }
+
+    /// True from a jump pad, ramp or knockback launch until the landing. These carry the player
+    /// up on their own, unlike a jump that can be cut short by letting go.
+    pub fn is_launched(&self) -> bool {
+        matches!(
+            self.launch.or(self.pending_launch),
+            Some(LaunchKind::JumpPad | LaunchKind::Ramp | LaunchKind::Knockback)
+        )
+    }
+
+    pub fn is_airborne(&self) -> bool {
+        self.airborne
+    }
+
+    pub fn air_time(&self) -> f32 {
+        self.air_time
+    }
+
+    /// Rotation accumulated in the air, in radians.
+    pub fn rotation(&self) -> f32 {
+        self.rotation
+    }
+
+    /// Returns how the player landed on the frame they touch the ground again.
+    pub fn update(
+        &mut self,
+        grounded: bool,
+        vertical_velocity: f32,
+        angular_velocity: f32,
+        up_dot: f32,
+        rules: &AirtimeRules,
+        dt: f32,
+    ) -> Option<Landing> {
+        if !self.airborne {
+            if !grounded {
+                self.airborne = true;
+                self.air_time = 0.0;
+                self.rotation = 0.0;
+                self.launch = Some(self.pending_launch.take().unwrap_or(
+                    if vertical_velocity > rules.ramp_launch_speed { LaunchKind::Ramp } else { LaunchKind::Fall },
+                ));
+                if self.launch == Some(LaunchKind::Jump) {
+                    self.stats.jumps += 1;
+                }
+            }
+            return None;
+        }
+
+        if !grounded {
+            self.air_time += dt;
+            self.rotation += angular_velocity * dt;
+            return None;
+        }
+
+        self.airborne = false;
+        self.pending_launch = None;
+        let launch = self.launch.take().unwrap_or(LaunchKind::Fall);
+        let air_time = self.air_time;
+        self.stats.best_air_time = self.stats.best_air_time.max(air_time);
+        if up_dot < rules.upright_cos {
+            self.stats.crashes += 1;
+            return Some(Landing::Crash { launch, air_time });
+        }
+
+        let flips = (self.rotation.abs() / TAU).floor() as u32;
+        self.stats.flips += flips;
+        if air_time >= rules.big_air_time {
+            self.stats.big_airs += 1;
+            self.stats.score += (air_time * 10.0) as u32 + flips * 50;
+        }
+        Some(Landing::Upright { launch, air_time, flips })
+    }
+}
@@ -1,4 +1,3 @@
-
 use fyrox::{
     core::{uuid::{Uuid, uuid}, visitor::prelude::*, reflect::prelude::*, TypeUuidProvider},
     event::Event, impl_component_provider,
@@ -6,13 +5,39 @@ use fyrox::{
 };
 use fyrox::core::algebra::Vector3;
 use fyrox::core::pool::Handle;
+use fyrox::core::rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
 use fyrox::scene::camera::Camera;
 
 use fyrox::scene::node::Node;
+use fyrox::script::{ScriptMessageContext, ScriptMessagePayload};
+
+/// Shakes every camera following the player, e.g. after an explosion.
+#[derive(Debug, Clone)]
+pub struct CameraShakeMessage {
+    pub intensity: f32,
+    pub duration: f32,
+}
 
 #[derive(Visit, Reflect, Default, Debug, Clone)]
 pub struct CameraController {
-    follow_ref: Handle<Node>
+    follow_ref: Handle<Node>,
+    /// Seeds the shake, so replays and daily runs shake the same way.
+    #[visit(optional)]
+    pub seed: u64,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    shake_intensity: f32,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    shake_time: f32,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    shake_offset: Vector3<f32>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    rng: Option<ChaCha8Rng>,
 }
 
 impl_component_provider!(CameraController);
@@ -33,23 +58,41 @@ impl ScriptTrait for CameraController {
         camera_transform.set_position(Vector3::new(target_x, pos.y, pos.z));
     }
 
-    fn on_start(&mut self, _context: &mut ScriptContext) {}
+    fn on_start(&mut self, context: &mut ScriptContext) {
+        context.message_dispatcher.subscribe_to::<CameraShakeMessage>(context.handle);
+        self.rng = Some(ChaCha8Rng::seed_from_u64(self.seed));
+    }
 
     fn on_deinit(&mut self, _context: &mut ScriptDeinitContext) {}
 
     fn on_os_event(&mut self, _event: &Event<()>, _context: &mut ScriptContext) {}
 
     fn on_update(&mut self, context: &mut ScriptContext) {
+        let previous_offset = self.shake_offset;
+        self.shake_time -= context.dt;
+        self.shake_offset = if self.shake_time > 0.0 {
+            let rng = self.rng.get_or_insert_with(|| ChaCha8Rng::seed_from_u64(self.seed));
+            Vector3::new(0.0, rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0)).scale(self.shake_intensity)
+        } else {
+            Vector3::default()
+        };
+
         let graph = &mut context.scene.graph;
         let target_x = graph[self.follow_ref].local_transform().position().x;
         let camera = graph[context.handle].cast_mut::<Camera>().expect("Camera expected but got other");
         let camera_transform = camera.local_transform_mut();
-        let pos = camera_transform.position();
-        camera_transform.set_position(Vector3::new(target_x, pos.y, pos.z));
+        let pos = **camera_transform.position() - previous_offset;
+        camera_transform.set_position(Vector3::new(target_x, pos.y, pos.z) + self.shake_offset);
+    }
+
+    fn on_message(&mut self, message: &mut dyn ScriptMessagePayload, _ctx: &mut ScriptMessageContext) {
+        if let Some(shake) = message.downcast_ref::<CameraShakeMessage>() {
+            self.shake_intensity = shake.intensity;
+            self.shake_time = self.shake_time.max(shake.duration);
+        }
     }
 
     fn id(&self) -> Uuid {
         Self::type_uuid()
     }
 }
-    
\ No newline at end of file
@@ -0,0 +1,36 @@
+use fyrox::{
+    core::{uuid::{Uuid, uuid}, visitor::prelude::*, reflect::prelude::*, TypeUuidProvider},
+    event::Event, impl_component_provider,
+    script::{ScriptContext, ScriptDeinitContext, ScriptTrait},
+};
+
+/// Refills the player's battery while they overlap the station's collider.
+#[derive(Visit, Reflect, Default, Debug, Clone)]
+pub struct ChargingStation {
+    /// Charge restored per second of contact.
+    pub charge_rate: f32,
+}
+
+impl_component_provider!(ChargingStation);
+
+impl TypeUuidProvider for ChargingStation {
+    fn type_uuid() -> Uuid {
+        uuid!("b0e3c3f2-54a1-4f6e-9d0b-5f8f5c1a7e21")
+    }
+}
+
+impl ScriptTrait for ChargingStation {
+    fn on_init(&mut self, _context: &mut ScriptContext) {}
+
+    fn on_start(&mut self, _context: &mut ScriptContext) {}
+
+    fn on_deinit(&mut self, _context: &mut ScriptDeinitContext) {}
+
+    fn on_os_event(&mut self, _event: &Event<()>, _context: &mut ScriptContext) {}
+
+    fn on_update(&mut self, _context: &mut ScriptContext) {}
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+}
@@ -0,0 +1,67 @@
+use fyrox::{
+    core::{uuid::{Uuid, uuid}, visitor::prelude::*, reflect::prelude::*, TypeUuidProvider},
+    event::Event, impl_component_provider,
+    script::{ScriptContext, ScriptDeinitContext, ScriptTrait},
+};
+use fyrox::core::algebra::Vector3;
+use fyrox::core::pool::Handle;
+use fyrox::scene::graph::Graph;
+use fyrox::scene::node::Node;
+use crate::difficulty::DifficultyModifiers;
+use crate::package_stack::PackageStack;
+use crate::player_controller::MoveDirection;
+
+/// Snapshot of the player taken when a checkpoint is reached, restored when the player dies.
+#[derive(Debug, Clone)]
+pub struct CheckpointState {
+    pub position: Vector3<f32>,
+    pub direction: MoveDirection,
+    pub player_health: u8,
+    pub cargo: PackageStack,
+    pub battery_charge: f32,
+}
+
+#[derive(Visit, Reflect, Default, Debug, Clone)]
+pub struct Checkpoint {
+    /// Where the player respawns, the checkpoint itself is used when unset.
+    spawn_point: Handle<Node>,
+    /// Order of the checkpoint along the level, used to thin them out on harder difficulties.
+    pub index: u32,
+}
+
+impl Checkpoint {
+    pub fn is_active_for(&self, difficulty: &DifficultyModifiers) -> bool {
+        difficulty.keeps_checkpoint(self.index)
+    }
+
+    pub fn spawn_position(&self, handle: Handle<Node>, graph: &Graph) -> Vector3<f32> {
+        graph
+            .try_get(self.spawn_point)
+            .unwrap_or(&graph[handle])
+            .global_position()
+    }
+}
+
+impl_component_provider!(Checkpoint);
+
+impl TypeUuidProvider for Checkpoint {
+    fn type_uuid() -> Uuid {
+        uuid!("e1c2a7b4-3d5f-4b86-8a9e-0c6f2d4b7a13")
+    }
+}
+
+impl ScriptTrait for Checkpoint {
+    fn on_init(&mut self, _context: &mut ScriptContext) {}
+
+    fn on_start(&mut self, _context: &mut ScriptContext) {}
+
+    fn on_deinit(&mut self, _context: &mut ScriptDeinitContext) {}
+
+    fn on_os_event(&mut self, _event: &Event<()>, _context: &mut ScriptContext) {}
+
+    fn on_update(&mut self, _context: &mut ScriptContext) {}
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+}
@@ -0,0 +1,220 @@
+use fyrox::{
+    core::{uuid::{Uuid, uuid}, visitor::prelude::*, reflect::prelude::*, TypeUuidProvider},
+    event::Event, impl_component_provider,
+    script::{ScriptContext, ScriptDeinitContext, ScriptTrait},
+};
+use fyrox::core::pool::Handle;
+use fyrox::scene::graph::Graph;
+use fyrox::scene::node::Node;
+use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
+use crate::contacts::{contact_events, ContactPhase};
+use crate::collectible::CollectibleEffect::Magnet;
+use crate::collectible::CollectiblePersistence::{PersistForLevel, Respawn};
+use crate::player_controller::PlayerController;
+
+#[derive(Debug, Visit, Reflect, Clone, PartialEq, AsRefStr, EnumString, EnumVariantNames)]
+pub enum CollectibleEffect {
+    /// Adds `amount` to the battery charge.
+    RestoreCharge,
+    /// Adds `amount` health to every carried package.
+    RepairPackage,
+    /// Ignores all damage for `duration` seconds.
+    Shield,
+    /// Raises max speed by `amount` for `duration` seconds, on top of terrain effects.
+    SpeedBoost,
+    /// Pulls collectibles within `amount` units towards the player for `duration` seconds.
+    Magnet,
+}
+
+impl Default for CollectibleEffect {
+    fn default() -> Self {
+        CollectibleEffect::RestoreCharge
+    }
+}
+
+#[derive(Debug, Visit, Reflect, Clone, PartialEq, AsRefStr, EnumString, EnumVariantNames)]
+pub enum CollectiblePersistence {
+    /// Comes back after `respawn_time` seconds.
+    Respawn,
+    /// Stays collected for the rest of the level.
+    PersistForLevel,
+}
+
+impl Default for CollectiblePersistence {
+    fn default() -> Self {
+        PersistForLevel
+    }
+}
+
+/// Sent to the player when they pick up a collectible.
+#[derive(Debug, Clone)]
+pub struct CollectibleMessage {
+    pub effect: CollectibleEffect,
+    pub amount: f32,
+    pub duration: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct ActivePowerUp {
+    pub effect: CollectibleEffect,
+    pub strength: f32,
+    pub remaining: f32,
+}
+
+/// Timed effects currently applied to the player.
+#[derive(Default, Debug, Clone)]
+pub struct PowerUps {
+    active: Vec<ActivePowerUp>,
+}
+
+impl PowerUps {
+    /// Picking up an effect that is already running refreshes it instead of stacking it.
+    pub fn activate(&mut self, effect: CollectibleEffect, strength: f32, duration: f32) {
+        if let Some(power_up) = self.active.iter_mut().find(|p| p.effect == effect) {
+            power_up.strength = power_up.strength.max(strength);
+            power_up.remaining = power_up.remaining.max(duration);
+        } else {
+            self.active.push(ActivePowerUp { effect, strength, remaining: duration });
+        }
+    }
+
+    pub fn tick(&mut self, dt: f32) {
+        for power_up in self.active.iter_mut() {
+            power_up.remaining -= dt;
+        }
+        self.active.retain(|p| p.remaining > 0.0);
+    }
+
+    pub fn strength(&self, effect: CollectibleEffect) -> Option<f32> {
+        self.active.iter().find(|p| p.effect == effect).map(|p| p.strength)
+    }
+
+    pub fn is_active(&self, effect: CollectibleEffect) -> bool {
+        self.strength(effect).is_some()
+    }
+
+    pub fn active(&self) -> &[ActivePowerUp] {
+        &self.active
+    }
+
+    pub fn clear(&mut self) {
+        self.active.clear();
+    }
+}
+
+#[derive(Visit, Reflect, Default, Debug, Clone)]
+pub struct Collectible {
+    pub effect: CollectibleEffect,
+    pub amount: f32,
+    pub duration: f32,
+    pub persistence: CollectiblePersistence,
+    pub respawn_time: f32,
+
+    pickup_sound: Handle<Node>,
+    model: Handle<Node>,
+    /// How fast the collectible flies towards the player while a magnet is active.
+    magnet_speed: f32,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    collected: bool,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    respawn_timer: f32,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    player: Handle<Node>,
+}
+
+impl Collectible {
+    pub fn is_collected(&self) -> bool {
+        self.collected
+    }
+
+    fn set_collected(&mut self, collected: bool, graph: &mut Graph) {
+        self.collected = collected;
+        if let Some(model) = graph.try_get_mut(self.model) {
+            model.set_visibility(!collected);
+        }
+    }
+
+    fn follow_magnet(&self, handle: Handle<Node>, graph: &mut Graph, dt: f32) {
+        let radius = match graph
+            .try_get(self.player)
+            .and_then(|n| n.try_get_script::<PlayerController>())
+            .and_then(|p| p.power_ups.strength(Magnet))
+        {
+            Some(radius) => radius,
+            None => return,
+        };
+        let offset = graph[self.player].global_position() - graph[handle].global_position();
+        let distance = offset.norm();
+        if distance > f32::EPSILON && distance <= radius {
+            let step = offset.scale((self.magnet_speed * dt).min(distance) / distance);
+            let transform = graph[handle].local_transform_mut();
+            let position = **transform.position();
+            transform.set_position(position + step);
+        }
+    }
+}
+
+impl_component_provider!(Collectible);
+
+impl TypeUuidProvider for Collectible {
+    fn type_uuid() -> Uuid {
+        uuid!("5d7b0f9e-2c41-4a3b-bf6e-71c9a8d2e4f0")
+    }
+}
+
+impl ScriptTrait for Collectible {
+    fn on_init(&mut self, _context: &mut ScriptContext) {}
+
+    fn on_start(&mut self, context: &mut ScriptContext) {
+        if let Some((player, _)) = context.scene.graph.find_from_root(&mut |n| n.has_script::<PlayerController>()) {
+            self.player = player;
+        }
+    }
+
+    fn on_deinit(&mut self, _context: &mut ScriptDeinitContext) {}
+
+    fn on_os_event(&mut self, _event: &Event<()>, _context: &mut ScriptContext) {}
+
+    fn on_update(&mut self, context: &mut ScriptContext) {
+        let graph = &mut context.scene.graph;
+        if self.collected {
+            if self.persistence == Respawn {
+                self.respawn_timer -= context.dt;
+                // A player still overlapping keeps its contact, so it has to step off and back on
+                // before the respawned collectible sees another Enter.
+                if self.respawn_timer <= 0.0 {
+                    self.set_collected(false, graph);
+                }
+            }
+            return;
+        }
+
+        self.follow_magnet(context.handle, graph, context.dt);
+
+        let entered = contact_events(context.plugins, context.handle)
+            .into_iter()
+            .find(|e| e.phase == ContactPhase::Enter)
+            .map(|e| e.contact.player);
+        if let Some(player) = entered {
+            context.message_sender.send_to_target(player, CollectibleMessage {
+                effect: self.effect.clone(),
+                amount: self.amount,
+                duration: self.duration,
+            });
+            if let Some(sound) = graph.try_get_mut(self.pickup_sound) {
+                sound.as_sound_mut().stop();
+                sound.as_sound_mut().play();
+            }
+            self.respawn_timer = self.respawn_time;
+            self.set_collected(true, graph);
+        }
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+}
@@ -0,0 +1,177 @@
+//! Gathers the player's contacts and intersections once per physics step and hands them out to
+//! the scripts that need them, instead of every zone scanning its own collider each frame.
+use fyrox::core::pool::Handle;
+use fyrox::plugin::Plugin;
+use fyrox::scene::collider::Collider;
+use fyrox::scene::graph::Graph;
+use fyrox::scene::node::Node;
+use crate::charging_station::ChargingStation;
+use crate::checkpoint::Checkpoint;
+use crate::collectible::Collectible;
+use crate::jump_pad::JumpPad;
+use crate::package_pickup_point::PackagePickupPoint;
+use crate::player_controller::PlayerController;
+use crate::reverse_direction::ReverseDirection;
+use crate::terrain_effect::TerrainEffect;
+use crate::trigger::{player_in_sensor, player_touching, Trigger};
+use crate::Game;
+
+/// What the player ran into, decided by the script on the other collider.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContactKind {
+    Terrain,
+    PackagePoint,
+    ChargingStation,
+    Checkpoint,
+    Collectible,
+    JumpPad,
+    ReverseDirection,
+    Trigger,
+    Other,
+}
+
+impl ContactKind {
+    pub fn classify(node: &Node) -> Self {
+        if node.has_script::<TerrainEffect>() {
+            ContactKind::Terrain
+        } else if node.has_script::<PackagePickupPoint>() {
+            ContactKind::PackagePoint
+        } else if node.has_script::<ChargingStation>() {
+            ContactKind::ChargingStation
+        } else if node.has_script::<Checkpoint>() {
+            ContactKind::Checkpoint
+        } else if node.has_script::<Collectible>() {
+            ContactKind::Collectible
+        } else if node.has_script::<JumpPad>() {
+            ContactKind::JumpPad
+        } else if node.has_script::<ReverseDirection>() {
+            ContactKind::ReverseDirection
+        } else if node.has_script::<Trigger>() {
+            ContactKind::Trigger
+        } else {
+            ContactKind::Other
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContactPhase {
+    Enter,
+    Stay,
+    Exit,
+}
+
+/// A player's collider touching (solid) or overlapping (sensor) another collider.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Contact {
+    pub player: Handle<Node>,
+    pub collider: Handle<Node>,
+    pub kind: ContactKind,
+    pub sensor: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContactEvent {
+    pub contact: Contact,
+    pub phase: ContactPhase,
+}
+
+#[derive(Default, Debug, Clone)]
+pub struct ContactService {
+    contacts: Vec<Contact>,
+    events: Vec<ContactEvent>,
+    previous: Vec<Contact>,
+}
+
+impl ContactService {
+    /// The service of the running game, if the game plugin is loaded.
+    pub fn find(plugins: &[Box<dyn Plugin>]) -> Option<&ContactService> {
+        plugins.iter().find_map(|p| p.cast::<Game>()).map(|game| &game.contacts)
+    }
+
+    /// Collects this step's contacts of every player and works out which ones started or ended.
+    pub fn update(&mut self, graph: &Graph) {
+        std::mem::swap(&mut self.previous, &mut self.contacts);
+        self.contacts.clear();
+        self.events.clear();
+
+        for (player, node) in graph.pair_iter() {
+            if let Some(controller) = node.try_get_script::<PlayerController>() {
+                gather(player, controller.collider(), graph, &mut self.contacts);
+            }
+        }
+
+        for contact in self.contacts.iter() {
+            let phase = if self.previous.contains(contact) { ContactPhase::Stay } else { ContactPhase::Enter };
+            self.events.push(ContactEvent { contact: *contact, phase });
+        }
+        for contact in self.previous.iter().filter(|c| !self.contacts.contains(c)) {
+            self.events.push(ContactEvent { contact: *contact, phase: ContactPhase::Exit });
+        }
+    }
+
+    pub fn contacts(&self) -> &[Contact] {
+        &self.contacts
+    }
+
+    /// Everything the given player is touching or overlapping this step.
+    pub fn contacts_of(&self, player: Handle<Node>) -> impl Iterator<Item = &Contact> {
+        self.contacts.iter().filter(move |c| c.player == player)
+    }
+
+    /// Enter, stay and exit events involving the given collider, in a stable order.
+    pub fn events_for(&self, collider: Handle<Node>) -> impl Iterator<Item = &ContactEvent> {
+        self.events.iter().filter(move |e| e.contact.collider == collider)
+    }
+
+    /// The player overlapping or touching the given collider, if any.
+    pub fn player_at(&self, collider: Handle<Node>) -> Option<Handle<Node>> {
+        self.contacts.iter().find(|c| c.collider == collider).map(|c| c.player)
+    }
+}
+
+/// The player at the given collider, taken from the contact service when the game runs and from
+/// the collider itself otherwise.
+pub fn find_player(plugins: &[Box<dyn Plugin>], collider: Handle<Node>, graph: &Graph) -> Option<Handle<Node>> {
+    match ContactService::find(plugins) {
+        Some(service) => service.player_at(collider),
+        None => player_in_sensor(collider, graph).or_else(|| player_touching(collider, graph)),
+    }
+}
+
+/// Enter, stay and exit events at the given collider this step. Empty without the game plugin,
+/// e.g. in the editor.
+pub fn contact_events(plugins: &[Box<dyn Plugin>], collider: Handle<Node>) -> Vec<ContactEvent> {
+    ContactService::find(plugins)
+        .map(|service| service.events_for(collider).copied().collect())
+        .unwrap_or_default()
+}
+
+/// Appends every collider the player's `handle` collider touches or overlaps.
+pub fn gather(player: Handle<Node>, handle: Handle<Node>, graph: &Graph, contacts: &mut Vec<Contact>) {
+    let collider = match graph.try_get_of_type::<Collider>(handle) {
+        Some(collider) => collider,
+        None => return,
+    };
+    let pairs = collider
+        .contacts(&graph.physics)
+        .filter(|c| c.has_any_active_contact)
+        .map(|c| (c.collider1, c.collider2, false))
+        .chain(
+            collider
+                .intersects(&graph.physics)
+                .filter(|c| c.has_any_active_contact)
+                .map(|c| (c.collider1, c.collider2, true)),
+        );
+    for (collider1, collider2, sensor) in pairs {
+        let other = if collider1.eq(&handle) { collider2 } else { collider1 };
+        if let Some(node) = graph.try_get(other) {
+            contacts.push(Contact {
+                player,
+                collider: other,
+                kind: ContactKind::classify(node),
+                sensor,
+            });
+        }
+    }
+}
@@ -0,0 +1,123 @@
+//! Daily challenge: the date picks a seed, the seed picks the level and its modifiers, so
+//! everyone playing on the same day gets the same run. The generator is ChaCha8 rather than
+//! `StdRng`, whose output may change with a rand update.
+use std::time::{SystemTime, UNIX_EPOCH};
+use fyrox::core::rand::{seq::SliceRandom, Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+use strum_macros::AsRefStr;
+use crate::difficulty::DifficultyModifiers;
+use crate::time_trial::Medal;
+
+/// Levels the daily challenge picks from.
+pub const DAILY_LEVELS: [&str; 2] = ["data/scene.rgs", "data/westonscene1.rgs"];
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, AsRefStr)]
+pub enum ChallengeModifier {
+    DoubleDamage,
+    LowBattery,
+    FragilePackages,
+}
+
+impl ChallengeModifier {
+    pub const ALL: [ChallengeModifier; 3] = [
+        ChallengeModifier::DoubleDamage,
+        ChallengeModifier::LowBattery,
+        ChallengeModifier::FragilePackages,
+    ];
+
+    pub fn apply(&self, modifiers: &mut DifficultyModifiers) {
+        match self {
+            ChallengeModifier::DoubleDamage => modifiers.damage *= 2.0,
+            ChallengeModifier::LowBattery => modifiers.starting_battery *= 0.3,
+            ChallengeModifier::FragilePackages => modifiers.package_health *= 0.5,
+        }
+    }
+}
+
+/// Today's date as `YYYY-MM-DD` in UTC.
+pub fn today() -> String {
+    let days = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() / 86_400) as i64;
+    let (year, month, day) = civil_from_days(days);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Converts days since 1970-01-01 to a calendar date.
+pub fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// FNV-1a, stable across platforms and compiler versions unlike the std hasher.
+pub fn seed_from_date(date: &str) -> u64 {
+    date.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DailyChallenge {
+    pub date: String,
+    pub seed: u64,
+    pub level: String,
+    pub modifiers: Vec<ChallengeModifier>,
+}
+
+impl DailyChallenge {
+    pub fn for_date(date: &str) -> Self {
+        let seed = seed_from_date(date);
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let level = DAILY_LEVELS[rng.gen_range(0..DAILY_LEVELS.len())].to_string();
+        let count = rng.gen_range(1..=2);
+        let mut modifiers = ChallengeModifier::ALL
+            .choose_multiple(&mut rng, count)
+            .copied()
+            .collect::<Vec<_>>();
+        modifiers.sort_by_key(|m| ChallengeModifier::ALL.iter().position(|a| a == m));
+        Self {
+            date: date.to_string(),
+            seed,
+            level,
+            modifiers,
+        }
+    }
+
+    pub fn today() -> Self {
+        Self::for_date(&today())
+    }
+
+    pub fn apply(&self, modifiers: &mut DifficultyModifiers) {
+        for modifier in self.modifiers.iter() {
+            modifier.apply(modifiers);
+        }
+    }
+
+    pub fn description(&self) -> String {
+        format!(
+            "Daily {}: {}",
+            self.date,
+            self.modifiers.iter().map(|m| m.as_ref()).collect::<Vec<_>>().join(", ")
+        )
+    }
+}
+
+/// The scored attempt of a day, kept in the profile.
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct DailyResult {
+    pub level: String,
+    /// None while the attempt is running or when it wasn't finished.
+    pub time: Option<f32>,
+    pub deliveries: usize,
+    pub medal: Option<Medal>,
+}
@@ -0,0 +1,276 @@
+use fyrox::{
+    core::{uuid::{Uuid, uuid}, visitor::prelude::*, reflect::prelude::*, TypeUuidProvider},
+    event::Event, impl_component_provider,
+    script::{ScriptContext, ScriptDeinitContext, ScriptTrait},
+};
+use fyrox::core::algebra::Vector3;
+use fyrox::core::pool::Handle;
+use fyrox::core::rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use fyrox::resource::model::{ModelResource, ModelResourceExtension};
+use fyrox::scene::collider::Collider;
+use fyrox::scene::graph::Graph;
+use fyrox::scene::node::Node;
+use fyrox::scene::rigidbody::{RigidBody, RigidBodyType};
+use fyrox::script::{ScriptMessageContext, ScriptMessagePayload};
+use crate::camera_controller::CameraShakeMessage;
+use crate::player_controller::PlayerController;
+use crate::terrain_effect::DamageMessage;
+
+/// Sent when the player respawns, resettable props go back to how the level started.
+#[derive(Debug, Clone)]
+pub struct LevelResetMessage;
+
+/// A prop that breaks into debris when hit hard enough, and optionally explodes.
+#[derive(Visit, Reflect, Default, Debug, Clone)]
+pub struct Destructible {
+    pub health: f32,
+    /// Relative speed an impact needs before it does any damage.
+    pub impact_threshold: f32,
+    /// Health lost per unit of speed above the threshold.
+    pub impact_damage: f32,
+    collider: Handle<Node>,
+    model: Handle<Node>,
+
+    debris_prefab: Option<ModelResource>,
+    pub debris_count: u32,
+    pub debris_speed: f32,
+    particles: Handle<Node>,
+
+    pub explosive: bool,
+    pub explosion_radius: f32,
+    pub explosion_impulse: f32,
+    pub player_damage: u8,
+    pub package_damage: u8,
+    /// One of these is picked at random when the prop explodes.
+    explosion_sounds: Vec<Handle<Node>>,
+    pub camera_shake: f32,
+    pub camera_shake_duration: f32,
+
+    pub respawn_on_reset: bool,
+    /// Seeds the debris directions and sound picks, so explosions play out the same every run.
+    #[visit(optional)]
+    pub seed: u64,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    current_health: f32,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    broken: bool,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    touching: Vec<Handle<Node>>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    debris: Vec<Handle<Node>>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    start_position: Vector3<f32>,
+    /// Body type to go back to on reset, the body is parked as static while broken.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    body_type: Option<RigidBodyType>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    rng: Option<ChaCha8Rng>,
+}
+
+fn body_velocity(graph: &Graph, handle: Handle<Node>) -> Vector3<f32> {
+    graph
+        .find_up(handle, &mut |n| n.cast::<RigidBody>().is_some())
+        .and_then(|(_, n)| n.cast::<RigidBody>())
+        .map(|b| b.lin_vel())
+        .unwrap_or_default()
+}
+
+impl Destructible {
+    pub fn is_broken(&self) -> bool {
+        self.broken
+    }
+
+    /// Speeds of impacts that started this frame, relative to this prop.
+    fn new_impacts(&mut self, handle: Handle<Node>, graph: &Graph) -> Vec<f32> {
+        let mut impacts = Vec::new();
+        let mut touching = Vec::new();
+        if let Some(collider) = graph.try_get_of_type::<Collider>(self.collider) {
+            let own_velocity = body_velocity(graph, handle);
+            for contact in collider.contacts(&graph.physics) {
+                if contact.has_any_active_contact {
+                    let opposing_handle = if contact.collider1.eq(&self.collider) { contact.collider2 } else { contact.collider1 };
+                    if !self.touching.contains(&opposing_handle) {
+                        impacts.push((body_velocity(graph, opposing_handle) - own_velocity).norm());
+                    }
+                    touching.push(opposing_handle);
+                }
+            }
+        }
+        self.touching = touching;
+        impacts
+    }
+
+    fn set_intact(&mut self, intact: bool, handle: Handle<Node>, graph: &mut Graph) {
+        self.broken = !intact;
+        // With its collider turned into a sensor a dynamic body would fall through the world.
+        if let Some(body) = graph[handle].cast_mut::<RigidBody>() {
+            if intact {
+                if let Some(body_type) = self.body_type.take() {
+                    body.set_body_type(body_type);
+                }
+            } else {
+                self.body_type.get_or_insert(body.body_type());
+                body.set_body_type(RigidBodyType::Static);
+            }
+        }
+        if let Some(model) = graph.try_get_mut(self.model) {
+            model.set_visibility(intact);
+        }
+        if let Some(collider) = graph.try_get_mut_of_type::<Collider>(self.collider) {
+            collider.set_is_sensor(!intact);
+        }
+    }
+
+    fn spawn_debris(&mut self, position: Vector3<f32>, context: &mut ScriptContext) {
+        let prefab = match self.debris_prefab.as_ref() {
+            Some(prefab) => prefab.clone(),
+            None => return,
+        };
+        let rng = self.rng.get_or_insert_with(|| ChaCha8Rng::seed_from_u64(self.seed));
+        for _ in 0..self.debris_count {
+            let piece = prefab.instantiate(context.scene);
+            let graph = &mut context.scene.graph;
+            graph[piece].local_transform_mut().set_position(position);
+            if let Some(body) = graph[piece].cast_mut::<RigidBody>() {
+                let direction = Vector3::new(rng.gen_range(-1.0..1.0), rng.gen_range(0.2..1.0), 0.0);
+                body.set_lin_vel(direction.normalize().scale(self.debris_speed));
+            }
+            self.debris.push(piece);
+        }
+    }
+
+    fn explode(&mut self, handle: Handle<Node>, position: Vector3<f32>, context: &mut ScriptContext) {
+        let graph = &mut context.scene.graph;
+        let mut players = Vec::new();
+        for (node_handle, node) in graph.pair_iter_mut() {
+            if node_handle == handle || self.debris.contains(&node_handle) {
+                continue;
+            }
+            let offset = node.global_position() - position;
+            let distance = offset.norm();
+            if distance > self.explosion_radius {
+                continue;
+            }
+            if node.has_script::<PlayerController>() {
+                players.push(node_handle);
+            }
+            if let Some(body) = node.cast_mut::<RigidBody>() {
+                let falloff = 1.0 - distance / self.explosion_radius.max(f32::EPSILON);
+                let direction = offset.try_normalize(f32::EPSILON).unwrap_or_else(Vector3::y);
+                body.apply_impulse(direction.scale(self.explosion_impulse * falloff));
+                body.wake_up();
+            }
+        }
+        for player in players {
+            context.message_sender.send_to_target(player, DamageMessage {
+                player_damage: self.player_damage,
+                package_damage: self.package_damage,
+            });
+        }
+
+        if !self.explosion_sounds.is_empty() {
+            let rng = self.rng.get_or_insert_with(|| ChaCha8Rng::seed_from_u64(self.seed));
+            let sound = self.explosion_sounds[rng.gen_range(0..self.explosion_sounds.len())];
+            if let Some(sound) = graph.try_get_mut(sound) {
+                sound.as_sound_mut().stop();
+                sound.as_sound_mut().play();
+            }
+        }
+        if self.camera_shake > 0.0 {
+            context.message_sender.send_global(CameraShakeMessage {
+                intensity: self.camera_shake,
+                duration: self.camera_shake_duration,
+            });
+        }
+    }
+
+    fn break_apart(&mut self, handle: Handle<Node>, context: &mut ScriptContext) {
+        let position = context.scene.graph[handle].global_position();
+        self.set_intact(false, handle, &mut context.scene.graph);
+        if let Some(particles) = context.scene.graph.try_get_mut(self.particles) {
+            particles.set_enabled(true);
+        }
+        self.spawn_debris(position, context);
+        if self.explosive {
+            self.explode(handle, position, context);
+        }
+    }
+
+    fn reset(&mut self, handle: Handle<Node>, graph: &mut Graph) {
+        for piece in self.debris.drain(..) {
+            if graph.is_valid_handle(piece) {
+                graph.remove_node(piece);
+            }
+        }
+        if let Some(particles) = graph.try_get_mut(self.particles) {
+            particles.set_enabled(false);
+        }
+        if let Some(body) = graph[handle].cast_mut::<RigidBody>() {
+            body.set_lin_vel(Vector3::default());
+            body.set_ang_vel(Vector3::default());
+        }
+        graph[handle].local_transform_mut().set_position(self.start_position);
+        self.current_health = self.health;
+        self.touching.clear();
+        self.set_intact(true, handle, graph);
+    }
+}
+
+impl_component_provider!(Destructible);
+
+impl TypeUuidProvider for Destructible {
+    fn type_uuid() -> Uuid {
+        uuid!("d8a41f67-2e9b-4c05-8b3d-7f1e6a9c2b48")
+    }
+}
+
+impl ScriptTrait for Destructible {
+    fn on_init(&mut self, _context: &mut ScriptContext) {}
+
+    fn on_start(&mut self, context: &mut ScriptContext) {
+        context.message_dispatcher.subscribe_to::<LevelResetMessage>(context.handle);
+        self.current_health = self.health;
+        self.rng = Some(ChaCha8Rng::seed_from_u64(self.seed));
+        self.start_position = **context.scene.graph[context.handle].local_transform().position();
+        if let Some(particles) = context.scene.graph.try_get_mut(self.particles) {
+            particles.set_enabled(false);
+        }
+    }
+
+    fn on_deinit(&mut self, _context: &mut ScriptDeinitContext) {}
+
+    fn on_os_event(&mut self, _event: &Event<()>, _context: &mut ScriptContext) {}
+
+    fn on_update(&mut self, context: &mut ScriptContext) {
+        if self.broken {
+            return;
+        }
+        for speed in self.new_impacts(context.handle, &context.scene.graph) {
+            if speed > self.impact_threshold {
+                self.current_health -= (speed - self.impact_threshold) * self.impact_damage;
+            }
+        }
+        if self.current_health <= 0.0 {
+            self.break_apart(context.handle, context);
+        }
+    }
+
+    fn on_message(&mut self, message: &mut dyn ScriptMessagePayload, ctx: &mut ScriptMessageContext) {
+        if message.downcast_ref::<LevelResetMessage>().is_some() && self.respawn_on_reset {
+            self.reset(ctx.handle, &mut ctx.scene.graph);
+        }
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+}
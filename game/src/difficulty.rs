@@ -0,0 +1,172 @@
+use serde::{Deserialize, Serialize};
+use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, AsRefStr, EnumString, EnumVariantNames)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+    Custom,
+}
+
+impl Default for Difficulty {
+    fn default() -> Self {
+        Difficulty::Normal
+    }
+}
+
+/// Multipliers applied centrally to damage, health and timers. Normal leaves every value unchanged.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct DifficultyModifiers {
+    pub damage: f32,
+    pub player_health: f32,
+    pub package_health: f32,
+    pub delivery_deadline: f32,
+    /// Fraction of the level's checkpoints that stay active, between 0 and 1.
+    pub checkpoint_density: f32,
+    /// Fraction of the battery that is charged at the start.
+    pub starting_battery: f32,
+}
+
+impl Default for DifficultyModifiers {
+    fn default() -> Self {
+        Self {
+            damage: 1.0,
+            player_health: 1.0,
+            package_health: 1.0,
+            delivery_deadline: 1.0,
+            checkpoint_density: 1.0,
+            starting_battery: 1.0,
+        }
+    }
+}
+
+impl Difficulty {
+    pub fn modifiers(&self, custom: &DifficultyModifiers) -> DifficultyModifiers {
+        match self {
+            Difficulty::Easy => DifficultyModifiers {
+                damage: 0.5,
+                player_health: 1.5,
+                package_health: 1.5,
+                delivery_deadline: 1.5,
+                checkpoint_density: 1.0,
+                starting_battery: 1.0,
+            },
+            Difficulty::Normal => DifficultyModifiers::default(),
+            Difficulty::Hard => DifficultyModifiers {
+                damage: 1.5,
+                player_health: 0.75,
+                package_health: 0.75,
+                delivery_deadline: 0.75,
+                checkpoint_density: 0.5,
+                starting_battery: 1.0,
+            },
+            Difficulty::Custom => custom.clone(),
+        }
+    }
+
+    /// Orders the presets from easiest to hardest. Custom can be anything, so it ranks lowest.
+    pub fn rank(&self) -> u8 {
+        match self {
+            Difficulty::Custom => 0,
+            Difficulty::Easy => 1,
+            Difficulty::Normal => 2,
+            Difficulty::Hard => 3,
+        }
+    }
+}
+
+impl DifficultyModifiers {
+    pub fn scale_damage(&self, damage: u8) -> u8 {
+        scale(damage, self.damage)
+    }
+
+    /// Starting health never drops to zero, otherwise the player would start dead.
+    pub fn scale_player_health(&self, health: u8) -> u8 {
+        scale(health, self.player_health).max(health.min(1))
+    }
+
+    pub fn scale_package_health(&self, health: u8) -> u8 {
+        scale(health, self.package_health).max(health.min(1))
+    }
+
+    pub fn scale_deadline(&self, seconds: f32) -> f32 {
+        seconds * self.delivery_deadline
+    }
+
+    /// Thins out checkpoints evenly, the n-th checkpoint is kept when it crosses a new multiple of
+    /// the density.
+    pub fn keeps_checkpoint(&self, index: u32) -> bool {
+        let density = self.checkpoint_density.clamp(0.0, 1.0);
+        ((index + 1) as f32 * density).floor() > (index as f32 * density).floor()
+    }
+}
+
+/// One value of the custom difficulty, as edited from the pause menu.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CustomSetting {
+    Damage,
+    PlayerHealth,
+    PackageHealth,
+    DeliveryDeadline,
+    CheckpointDensity,
+    StartingBattery,
+}
+
+impl CustomSetting {
+    pub const ALL: [CustomSetting; 6] = [
+        CustomSetting::Damage,
+        CustomSetting::PlayerHealth,
+        CustomSetting::PackageHealth,
+        CustomSetting::DeliveryDeadline,
+        CustomSetting::CheckpointDensity,
+        CustomSetting::StartingBattery,
+    ];
+
+    /// How much one press of the menu's - or + button changes the value.
+    pub const STEP: f32 = 0.25;
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            CustomSetting::Damage => "Damage",
+            CustomSetting::PlayerHealth => "Player health",
+            CustomSetting::PackageHealth => "Package health",
+            CustomSetting::DeliveryDeadline => "Deadlines",
+            CustomSetting::CheckpointDensity => "Checkpoints",
+            CustomSetting::StartingBattery => "Starting battery",
+        }
+    }
+
+    pub fn get(&self, modifiers: &DifficultyModifiers) -> f32 {
+        match self {
+            CustomSetting::Damage => modifiers.damage,
+            CustomSetting::PlayerHealth => modifiers.player_health,
+            CustomSetting::PackageHealth => modifiers.package_health,
+            CustomSetting::DeliveryDeadline => modifiers.delivery_deadline,
+            CustomSetting::CheckpointDensity => modifiers.checkpoint_density,
+            CustomSetting::StartingBattery => modifiers.starting_battery,
+        }
+    }
+
+    /// Changes the value by `delta`, multipliers stay between 0.25 and 3, fractions between 0 and 1.
+    pub fn adjust(&self, modifiers: &mut DifficultyModifiers, delta: f32) {
+        let (min, max) = match self {
+            CustomSetting::CheckpointDensity | CustomSetting::StartingBattery => (0.0, 1.0),
+            _ => (0.25, 3.0),
+        };
+        let value = (self.get(modifiers) + delta).clamp(min, max);
+        match self {
+            CustomSetting::Damage => modifiers.damage = value,
+            CustomSetting::PlayerHealth => modifiers.player_health = value,
+            CustomSetting::PackageHealth => modifiers.package_health = value,
+            CustomSetting::DeliveryDeadline => modifiers.delivery_deadline = value,
+            CustomSetting::CheckpointDensity => modifiers.checkpoint_density = value,
+            CustomSetting::StartingBattery => modifiers.starting_battery = value,
+        }
+    }
+}
+
+fn scale(value: u8, factor: f32) -> u8 {
+    (value as f32 * factor.max(0.0)).round().min(u8::MAX as f32) as u8
+}
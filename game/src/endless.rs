@@ -0,0 +1,344 @@
+//! Endless mode: lays out the level ahead of the player from chunk templates in a RON file and
+//! removes it again behind them. The same seed always gives the same level.
+use std::collections::{HashMap, VecDeque};
+use fyrox::{
+    core::{uuid::{Uuid, uuid}, visitor::prelude::*, reflect::prelude::*, TypeUuidProvider},
+    event::Event, impl_component_provider,
+    script::{ScriptContext, ScriptDeinitContext, ScriptTrait},
+};
+use fyrox::core::algebra::{Matrix4, Vector3};
+use fyrox::core::log::Log;
+use fyrox::core::pool::Handle;
+use fyrox::core::rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use fyrox::resource::model::{Model, ModelResource, ModelResourceExtension};
+use fyrox::scene::base::BaseBuilder;
+use fyrox::scene::collider::{ColliderBuilder, ColliderShape};
+use fyrox::scene::graph::Graph;
+use fyrox::scene::mesh::MeshBuilder;
+use fyrox::scene::mesh::surface::{SurfaceBuilder, SurfaceData, SurfaceSharedData};
+use fyrox::scene::node::Node;
+use fyrox::scene::rigidbody::{RigidBodyBuilder, RigidBodyType};
+use fyrox::scene::transform::TransformBuilder;
+use serde::{Deserialize, Serialize};
+use crate::player_controller::PlayerController;
+use crate::Game;
+
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct ChunkPiece {
+    pub prefab: String,
+    /// Position in the chunk, x runs along the direction of travel.
+    pub offset: [f32; 3],
+    /// Chance to appear at difficulty zero.
+    pub chance: f32,
+    /// Added to the chance at full difficulty.
+    pub chance_at_max_difficulty: f32,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct ChunkTemplate {
+    pub name: String,
+    pub length: f32,
+    pub weight: f32,
+    /// Difficulty range in which the chunk can be picked, difficulty goes from 0 to 1.
+    pub min_difficulty: f32,
+    pub max_difficulty: Option<f32>,
+    pub pieces: Vec<ChunkPiece>,
+}
+
+impl ChunkTemplate {
+    fn allows(&self, difficulty: f32) -> bool {
+        difficulty >= self.min_difficulty && self.max_difficulty.map_or(true, |max| difficulty <= max)
+    }
+}
+
+/// Chunk templates for the endless mode, stored in `data/endless.ron`.
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct EndlessLayout {
+    /// Distance at which the difficulty reaches its maximum.
+    pub max_difficulty_distance: f32,
+    pub chunks: Vec<ChunkTemplate>,
+}
+
+impl EndlessLayout {
+    pub fn from_ron(text: &str) -> Result<Self, ron::error::SpannedError> {
+        ron::from_str(text)
+    }
+
+    pub fn load(path: &str) -> Self {
+        match std::fs::read_to_string(path).map_err(|err| err.to_string())
+            .and_then(|text| Self::from_ron(&text).map_err(|err| err.to_string()))
+        {
+            Ok(layout) => layout,
+            Err(err) => {
+                Log::err(format!("Unable to load endless layout {}: {}", path, err));
+                Self::default()
+            }
+        }
+    }
+
+    pub fn difficulty_at(&self, distance: f32) -> f32 {
+        if self.max_difficulty_distance <= 0.0 {
+            return 1.0;
+        }
+        (distance / self.max_difficulty_distance).clamp(0.0, 1.0)
+    }
+}
+
+/// A chunk picked by the generator: its template and the pieces that made it in.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeneratedChunk {
+    pub template: usize,
+    pub start: f32,
+    pub length: f32,
+    pub pieces: Vec<ChunkPiece>,
+}
+
+/// Picks chunks one after another, only depending on the seed and the layout.
+#[derive(Debug, Clone)]
+pub struct EndlessGenerator {
+    layout: EndlessLayout,
+    rng: ChaCha8Rng,
+    distance: f32,
+}
+
+impl EndlessGenerator {
+    pub fn new(layout: EndlessLayout, seed: u64) -> Self {
+        Self {
+            layout,
+            rng: ChaCha8Rng::seed_from_u64(seed),
+            distance: 0.0,
+        }
+    }
+
+    /// Distance covered by the chunks generated so far.
+    pub fn distance(&self) -> f32 {
+        self.distance
+    }
+
+    pub fn next_chunk(&mut self) -> Option<GeneratedChunk> {
+        let difficulty = self.layout.difficulty_at(self.distance);
+        let candidates = self.layout.chunks.iter()
+            .enumerate()
+            .filter(|(_, c)| c.allows(difficulty) && c.weight > 0.0 && c.length > 0.0)
+            .collect::<Vec<_>>();
+        let total = candidates.iter().map(|(_, c)| c.weight).sum::<f32>();
+        if total <= 0.0 {
+            return None;
+        }
+        let mut pick = self.rng.gen_range(0.0..total);
+        let (template, chunk) = candidates.iter()
+            .find(|(_, c)| {
+                pick -= c.weight;
+                pick < 0.0
+            })
+            .copied()
+            .unwrap_or(*candidates.last()?);
+
+        let mut pieces = Vec::new();
+        for piece in chunk.pieces.iter() {
+            let chance = piece.chance + piece.chance_at_max_difficulty * difficulty;
+            // Always roll so the sequence doesn't depend on the chances.
+            if self.rng.gen::<f32>() < chance {
+                pieces.push(piece.clone());
+            }
+        }
+
+        let generated = GeneratedChunk {
+            template,
+            start: self.distance,
+            length: chunk.length,
+            pieces,
+        };
+        self.distance += chunk.length;
+        Some(generated)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct SpawnedChunk {
+    end: f32,
+    nodes: Vec<Handle<Node>>,
+}
+
+/// Put on a node at the start of an otherwise empty level, it builds the track from there on.
+#[derive(Visit, Reflect, Debug, Clone)]
+pub struct EndlessMode {
+    pub layout: String,
+    pub seed: u64,
+    /// How far ahead of the player the track is built.
+    pub view_ahead: f32,
+    /// How far behind the player chunks are kept before they are removed.
+    pub keep_behind: f32,
+    /// World x direction the track runs in, -1 matches the player's default direction.
+    pub direction: f32,
+    /// Spawned at the start of the track when the level has no player of its own.
+    #[visit(optional)]
+    pub player_prefab: String,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    generator: Option<EndlessGenerator>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    prefabs: HashMap<String, ModelResource>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    chunks: VecDeque<SpawnedChunk>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    origin: Vector3<f32>,
+}
+
+impl Default for EndlessMode {
+    fn default() -> Self {
+        Self {
+            layout: "data/endless.ron".to_string(),
+            seed: 0,
+            view_ahead: 60.0,
+            keep_behind: 20.0,
+            direction: -1.0,
+            player_prefab: String::new(),
+            generator: None,
+            prefabs: Default::default(),
+            chunks: Default::default(),
+            origin: Default::default(),
+        }
+    }
+}
+
+impl EndlessMode {
+    /// Distance the player has covered along the track.
+    pub fn distance(&self, graph: &Graph) -> Option<f32> {
+        let player = graph.find_from_root(&mut |n| n.has_script::<PlayerController>())?.1;
+        Some((player.global_position().x - self.origin.x) * self.direction.signum())
+    }
+
+    fn at(&self, distance: f32, offset: Vector3<f32>) -> Vector3<f32> {
+        self.origin + Vector3::new((distance + offset.x) * self.direction.signum(), offset.y, offset.z)
+    }
+
+    fn spawn_ground(&self, chunk: &GeneratedChunk, graph: &mut Graph) -> Handle<Node> {
+        let size = Vector3::new(chunk.length, 1.0, 2.0);
+        let mesh = MeshBuilder::new(BaseBuilder::new())
+            .with_surfaces(vec![SurfaceBuilder::new(SurfaceSharedData::new(
+                SurfaceData::make_cube(Matrix4::new_nonuniform_scaling(&size)),
+            ))
+            .build()])
+            .build(graph);
+        let collider = ColliderBuilder::new(BaseBuilder::new())
+            .with_shape(ColliderShape::cuboid(size.x / 2.0, size.y / 2.0, size.z / 2.0))
+            .build(graph);
+        let center = self.at(chunk.start, Vector3::new(chunk.length / 2.0, -0.5, 0.0));
+        RigidBodyBuilder::new(BaseBuilder::new()
+            .with_local_transform(TransformBuilder::new().with_local_position(center).build())
+            .with_children(&[mesh, collider]))
+            .with_body_type(RigidBodyType::Static)
+            .build(graph)
+    }
+
+    fn spawn(&mut self, chunk: GeneratedChunk, context: &mut ScriptContext) {
+        let mut nodes = vec![self.spawn_ground(&chunk, &mut context.scene.graph)];
+        for piece in chunk.pieces.iter() {
+            let prefab = match self.prefabs.get(&piece.prefab).filter(|p| p.is_ok()) {
+                Some(prefab) => prefab.clone(),
+                None => continue,
+            };
+            let node = prefab.instantiate(context.scene);
+            let position = self.at(chunk.start, Vector3::from(piece.offset));
+            context.scene.graph[node].local_transform_mut().set_position(position);
+            nodes.push(node);
+        }
+        self.chunks.push_back(SpawnedChunk {
+            end: chunk.start + chunk.length,
+            nodes,
+        });
+    }
+
+    fn spawn_player(&mut self, context: &mut ScriptContext) {
+        let prefab = match self.prefabs.get(&self.player_prefab).filter(|p| p.is_ok()) {
+            Some(prefab) => prefab.clone(),
+            None => return,
+        };
+        let player = prefab.instantiate(context.scene);
+        let position = self.at(0.0, Vector3::new(0.0, 1.0, 0.0));
+        context.scene.graph[player].local_transform_mut().set_position(position);
+        let controller = context.scene.graph
+            .find(player, &mut |n| n.has_script::<PlayerController>())
+            .map(|(handle, _)| handle);
+        let game = context.plugins.iter().find_map(|p| p.cast::<Game>());
+        if let (Some(controller), Some(game)) = (controller, game) {
+            if let Some(controller) = context.scene.graph[controller].try_get_script_mut::<PlayerController>() {
+                game.prepare_player(controller);
+            }
+        }
+    }
+}
+
+impl_component_provider!(EndlessMode);
+
+impl TypeUuidProvider for EndlessMode {
+    fn type_uuid() -> Uuid {
+        uuid!("4d6b1f93-a0e7-4c25-9b38-e7c52a14d860")
+    }
+}
+
+impl ScriptTrait for EndlessMode {
+    fn on_init(&mut self, _context: &mut ScriptContext) {}
+
+    fn on_start(&mut self, context: &mut ScriptContext) {
+        let layout = EndlessLayout::load(&self.layout);
+        let paths = layout.chunks.iter().flat_map(|c| c.pieces.iter()).map(|p| &p.prefab);
+        let has_player = context.scene.graph.linear_iter().any(|n| n.has_script::<PlayerController>());
+        for path in paths.chain(Some(&self.player_prefab).filter(|p| !p.is_empty() && !has_player)) {
+            if !self.prefabs.contains_key(path) {
+                let prefab = context.resource_manager.request::<Model, _>(path);
+                self.prefabs.insert(path.clone(), prefab);
+            }
+        }
+        self.generator = Some(EndlessGenerator::new(layout, self.seed));
+        self.origin = context.scene.graph[context.handle].global_position();
+    }
+
+    fn on_deinit(&mut self, _context: &mut ScriptDeinitContext) {}
+
+    fn on_os_event(&mut self, _event: &Event<()>, _context: &mut ScriptContext) {}
+
+    fn on_update(&mut self, context: &mut ScriptContext) {
+        // Wait for every prefab, so chunks look the same no matter how fast they load.
+        if self.prefabs.values().any(|p| p.is_loading()) {
+            return;
+        }
+        let distance = match self.distance(&context.scene.graph) {
+            Some(distance) => distance,
+            None => {
+                self.spawn_player(context);
+                return;
+            }
+        };
+
+        while self.generator.as_ref().map_or(false, |g| g.distance() < distance + self.view_ahead) {
+            match self.generator.as_mut().and_then(|g| g.next_chunk()) {
+                Some(chunk) => self.spawn(chunk, context),
+                None => break,
+            }
+        }
+
+        while self.chunks.front().map_or(false, |c| c.end < distance - self.keep_behind) {
+            if let Some(chunk) = self.chunks.pop_front() {
+                for node in chunk.nodes {
+                    if context.scene.graph.is_valid_handle(node) {
+                        context.scene.graph.remove_node(node);
+                    }
+                }
+            }
+        }
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+}
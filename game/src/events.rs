@@ -0,0 +1,140 @@
+//! Typed gameplay events. Scripts publish them through the game plugin, they are delivered once
+//! per frame in the order they were published, to observers first, then as global script messages
+//! to scripts that called `subscribe`, and to anyone reading `EventBus::delivered` during the next frame.
+use fyrox::core::pool::Handle;
+use fyrox::plugin::Plugin;
+use fyrox::scene::node::Node;
+use fyrox::script::{ScriptContext, ScriptMessageSender};
+use crate::player_controller::MoveDirection;
+use crate::Game;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GameplayEvent {
+    Damage {
+        player: Handle<Node>,
+        player_damage: u8,
+        package_damage: u8,
+    },
+    Pickup {
+        player: Handle<Node>,
+        point: Handle<Node>,
+        count: usize,
+    },
+    Delivery {
+        player: Handle<Node>,
+        point: Handle<Node>,
+        count: usize,
+        /// Health left in the delivered packages, and what they started with.
+        health: u32,
+        max_health: u32,
+    },
+    Death {
+        player: Handle<Node>,
+    },
+    Jump {
+        player: Handle<Node>,
+    },
+    Checkpoint {
+        player: Handle<Node>,
+        checkpoint: Handle<Node>,
+    },
+    DirectionChange {
+        player: Handle<Node>,
+        direction: MoveDirection,
+    },
+    /// Every drop-off in the level has been delivered to.
+    LevelCompleted,
+}
+
+/// An event as it was delivered, for debugging and tests.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedEvent {
+    pub frame: u64,
+    pub event: GameplayEvent,
+}
+
+/// Gets every delivered event, e.g. the plugin's statistics or achievements.
+pub trait GameplayObserver {
+    fn on_event(&mut self, event: &GameplayEvent);
+}
+
+#[derive(Default)]
+pub struct EventBus {
+    queue: Vec<GameplayEvent>,
+    delivered: Vec<GameplayEvent>,
+    observers: Vec<Box<dyn GameplayObserver>>,
+    /// Sends to the scripts of the running scene that subscribed.
+    sender: Option<ScriptMessageSender>,
+    recording: Option<Vec<RecordedEvent>>,
+    frame: u64,
+}
+
+impl EventBus {
+    /// The bus of the running game, if the game plugin is loaded.
+    pub fn find_mut(plugins: &mut [Box<dyn Plugin>]) -> Option<&mut EventBus> {
+        plugins.iter_mut().find_map(|p| p.cast_mut::<Game>()).map(|game| &mut game.events)
+    }
+
+    pub fn find(plugins: &[Box<dyn Plugin>]) -> Option<&EventBus> {
+        plugins.iter().find_map(|p| p.cast::<Game>()).map(|game| &game.events)
+    }
+
+    pub fn publish(&mut self, event: GameplayEvent) {
+        self.queue.push(event);
+    }
+
+    /// Observers are called in the order they were added.
+    pub fn observe(&mut self, observer: Box<dyn GameplayObserver>) {
+        self.observers.push(observer);
+    }
+
+    /// Delivers everything published since the last call.
+    pub fn dispatch(&mut self) {
+        self.frame += 1;
+        self.delivered.clear();
+        std::mem::swap(&mut self.queue, &mut self.delivered);
+        for event in self.delivered.iter() {
+            for observer in self.observers.iter_mut() {
+                observer.on_event(event);
+            }
+            if let Some(sender) = self.sender.as_ref() {
+                sender.send_global(event.clone());
+            }
+            if let Some(recording) = self.recording.as_mut() {
+                recording.push(RecordedEvent { frame: self.frame, event: event.clone() });
+            }
+        }
+    }
+
+    /// Events delivered by the last `dispatch`.
+    pub fn delivered(&self) -> &[GameplayEvent] {
+        &self.delivered
+    }
+
+    pub fn start_recording(&mut self) {
+        self.recording = Some(Vec::new());
+    }
+
+    pub fn stop_recording(&mut self) -> Vec<RecordedEvent> {
+        self.recording.take().unwrap_or_default()
+    }
+
+    pub fn recorded(&self) -> &[RecordedEvent] {
+        self.recording.as_deref().unwrap_or_default()
+    }
+}
+
+/// Lets a script receive delivered events in `on_message` as `GameplayEvent`s, call it from `on_start`.
+pub fn subscribe(context: &mut ScriptContext) {
+    context.message_dispatcher.subscribe_to::<GameplayEvent>(context.handle);
+    if let Some(bus) = EventBus::find_mut(context.plugins) {
+        bus.sender = Some(context.message_sender.clone());
+    }
+}
+
+/// Publishes on the running game's bus, does nothing when there is none, e.g. in the editor.
+pub fn publish(plugins: &mut [Box<dyn Plugin>], event: GameplayEvent) {
+    if let Some(bus) = EventBus::find_mut(plugins) {
+        bus.publish(event);
+    }
+}
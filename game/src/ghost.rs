@@ -0,0 +1,201 @@
+//! Races the player against their best run: samples the bike's transform while playing, keeps
+//! the fastest run per level on disk and plays it back on a translucent ghost bike.
+use std::path::{Path, PathBuf};
+use fyrox::{
+    core::{uuid::{Uuid, uuid}, visitor::prelude::*, reflect::prelude::*, TypeUuidProvider},
+    event::Event, impl_component_provider,
+    script::{ScriptContext, ScriptDeinitContext, ScriptTrait},
+};
+use fyrox::core::algebra::{Quaternion, UnitQuaternion, Vector3};
+use fyrox::core::log::Log;
+use serde::{Deserialize, Serialize};
+use crate::profile::SAVE_DIR;
+
+/// Frames between two samples, the ghost is interpolated in between.
+const SAMPLE_EVERY: u32 = 2;
+
+/// Marks the node that shows the ghost bike, give it a translucent material in the editor.
+#[derive(Visit, Reflect, Default, Debug, Clone)]
+pub struct Ghost {
+    /// Added to the ghost's position, e.g. to draw it behind the player.
+    pub offset: Vector3<f32>,
+}
+
+impl_component_provider!(Ghost);
+
+impl TypeUuidProvider for Ghost {
+    fn type_uuid() -> Uuid {
+        uuid!("e2a94c18-7f3d-4b05-8c6a-91d3f0b52e7c")
+    }
+}
+
+impl ScriptTrait for Ghost {
+    fn on_init(&mut self, _context: &mut ScriptContext) {}
+
+    fn on_start(&mut self, _context: &mut ScriptContext) {}
+
+    fn on_deinit(&mut self, _context: &mut ScriptDeinitContext) {}
+
+    fn on_os_event(&mut self, _event: &Event<()>, _context: &mut ScriptContext) {}
+
+    fn on_update(&mut self, _context: &mut ScriptContext) {}
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct GhostSample {
+    pub position: [f32; 3],
+    pub rotation: [f32; 4],
+}
+
+impl GhostSample {
+    pub fn new(position: Vector3<f32>, rotation: UnitQuaternion<f32>) -> Self {
+        let q = rotation.quaternion();
+        Self {
+            position: position.into(),
+            rotation: [q.i, q.j, q.k, q.w],
+        }
+    }
+
+    pub fn position(&self) -> Vector3<f32> {
+        Vector3::from(self.position)
+    }
+
+    pub fn rotation(&self) -> UnitQuaternion<f32> {
+        let [i, j, k, w] = self.rotation;
+        UnitQuaternion::from_quaternion(Quaternion::new(w, i, j, k))
+    }
+}
+
+/// One run through a level, stored as RON in the save folder.
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct GhostRun {
+    /// Seconds from the start to the finish.
+    pub time: f32,
+    /// Seconds between samples.
+    pub sample_interval: f32,
+    pub samples: Vec<GhostSample>,
+    /// Seconds at which each checkpoint was reached, in order.
+    pub checkpoint_times: Vec<f32>,
+}
+
+impl GhostRun {
+    pub fn path(level: &str) -> PathBuf {
+        Path::new(SAVE_DIR).join("ghosts").join(format!("{}.ghost", level))
+    }
+
+    pub fn load(path: &Path) -> Option<Self> {
+        let text = std::fs::read_to_string(path).ok()?;
+        ron::from_str(&text)
+            .map_err(|err| Log::err(format!("Unable to parse ghost {}: {}", path.display(), err)))
+            .ok()
+    }
+
+    pub fn save(&self, path: &Path) {
+        let result = ron::to_string(self)
+            .map_err(|err| err.to_string())
+            .and_then(|text| {
+                if let Some(dir) = path.parent() {
+                    std::fs::create_dir_all(dir).map_err(|err| err.to_string())?;
+                }
+                std::fs::write(path, text).map_err(|err| err.to_string())
+            });
+        if let Err(err) = result {
+            Log::err(format!("Unable to save ghost {}: {}", path.display(), err));
+        }
+    }
+
+    /// The ghost's transform at the given time, holding the last sample after the finish.
+    pub fn transform_at(&self, time: f32) -> Option<(Vector3<f32>, UnitQuaternion<f32>)> {
+        let last = self.samples.last()?;
+        let at = (time / self.sample_interval.max(f32::EPSILON)).max(0.0);
+        let index = at.floor() as usize;
+        if index + 1 >= self.samples.len() {
+            return Some((last.position(), last.rotation()));
+        }
+        let (a, b) = (&self.samples[index], &self.samples[index + 1]);
+        let t = at.fract();
+        Some((a.position().lerp(&b.position(), t), a.rotation().slerp(&b.rotation(), t)))
+    }
+}
+
+/// The run in progress and the best run it is compared to.
+#[derive(Default, Debug, Clone)]
+pub struct GhostRace {
+    level: String,
+    best: Option<GhostRun>,
+    current: GhostRun,
+    frame: u32,
+    finished: bool,
+    /// Seconds ahead (negative) or behind (positive) the best run at the last checkpoint.
+    delta: Option<f32>,
+}
+
+impl GhostRace {
+    /// Starts a new run and loads the best one for the level.
+    pub fn start(&mut self, level: &str, sample_interval: f32) {
+        *self = Self {
+            level: level.to_string(),
+            best: GhostRun::load(&GhostRun::path(level)),
+            current: GhostRun {
+                sample_interval: sample_interval * SAMPLE_EVERY as f32,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+    }
+
+    pub fn update(&mut self, dt: f32, position: Vector3<f32>, rotation: UnitQuaternion<f32>) {
+        if self.finished || self.level.is_empty() {
+            return;
+        }
+        if self.frame % SAMPLE_EVERY == 0 {
+            self.current.samples.push(GhostSample::new(position, rotation));
+        }
+        self.frame += 1;
+        self.current.time += dt;
+    }
+
+    pub fn ghost_transform(&self) -> Option<(Vector3<f32>, UnitQuaternion<f32>)> {
+        self.best.as_ref()?.transform_at(self.current.time)
+    }
+
+    pub fn checkpoint(&mut self) {
+        if self.finished {
+            return;
+        }
+        let index = self.current.checkpoint_times.len();
+        self.current.checkpoint_times.push(self.current.time);
+        self.delta = self
+            .best
+            .as_ref()
+            .and_then(|best| best.checkpoint_times.get(index))
+            .map(|best| self.current.time - best);
+    }
+
+    /// Ends the run, it replaces the stored ghost when it is faster. Returns true if it did.
+    pub fn finish(&mut self) -> bool {
+        if self.finished || self.level.is_empty() {
+            return false;
+        }
+        self.finished = true;
+        self.delta = self.best.as_ref().map(|best| self.current.time - best.time);
+        let improved = self.best.as_ref().map_or(true, |best| self.current.time < best.time);
+        if improved {
+            self.current.save(&GhostRun::path(&self.level));
+        }
+        improved
+    }
+
+    pub fn delta(&self) -> Option<f32> {
+        self.delta
+    }
+
+    pub fn time(&self) -> f32 {
+        self.current.time
+    }
+}
@@ -0,0 +1,168 @@
+//! Player input as a list of actions per frame, so runs can be recorded and replayed exactly.
+use std::path::{Path, PathBuf};
+use fyrox::core::log::Log;
+use fyrox::event::{ElementState, Event, VirtualKeyCode, WindowEvent};
+use fyrox::plugin::Plugin;
+use serde::{Deserialize, Serialize};
+use crate::profile::SAVE_DIR;
+use crate::Game;
+
+/// Length of one game step while recording or replaying. The physics use it too, so a replay
+/// takes the same steps regardless of the frame rate it runs at.
+pub const FIXED_TIMESTEP: f32 = 1.0 / 60.0;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputAction {
+    JumpPressed,
+    JumpReleased,
+    DropThrough,
+}
+
+impl InputAction {
+    pub fn from_key(keycode: VirtualKeyCode, state: ElementState) -> Option<Self> {
+        match (keycode, state) {
+            (VirtualKeyCode::Space, ElementState::Pressed) => Some(InputAction::JumpPressed),
+            (VirtualKeyCode::Space, ElementState::Released) => Some(InputAction::JumpReleased),
+            (VirtualKeyCode::Down | VirtualKeyCode::S, ElementState::Pressed) => Some(InputAction::DropThrough),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecordedInput {
+    pub frame: u64,
+    pub action: InputAction,
+}
+
+/// The inputs of one run, stored as RON.
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct InputRecording {
+    pub scene: String,
+    pub inputs: Vec<RecordedInput>,
+}
+
+impl InputRecording {
+    pub fn last_run_path() -> PathBuf {
+        Path::new(SAVE_DIR).join("last_run.replay")
+    }
+
+    pub fn load(path: &Path) -> Option<Self> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|err| Log::err(format!("Unable to read replay {}: {}", path.display(), err)))
+            .ok()?;
+        ron::from_str(&text)
+            .map_err(|err| Log::err(format!("Unable to parse replay {}: {}", path.display(), err)))
+            .ok()
+    }
+
+    pub fn save(&self, path: &Path) {
+        let result = ron::ser::to_string_pretty(self, Default::default())
+            .map_err(|err| err.to_string())
+            .and_then(|text| {
+                if let Some(dir) = path.parent() {
+                    std::fs::create_dir_all(dir).map_err(|err| err.to_string())?;
+                }
+                std::fs::write(path, text).map_err(|err| err.to_string())
+            });
+        if let Err(err) = result {
+            Log::err(format!("Unable to save replay {}: {}", path.display(), err));
+        }
+    }
+}
+
+/// Collects the actions for each frame, from the keyboard or from a replay, and records them.
+#[derive(Default, Debug, Clone)]
+pub struct InputState {
+    frame: u64,
+    pending: Vec<InputAction>,
+    current: Vec<InputAction>,
+    recording: InputRecording,
+    replay: Option<(InputRecording, usize)>,
+}
+
+impl InputState {
+    /// The input of the running game, if the game plugin is loaded.
+    pub fn find(plugins: &[Box<dyn Plugin>]) -> Option<&InputState> {
+        plugins.iter().find_map(|p| p.cast::<Game>()).map(|game| &game.input)
+    }
+
+    pub fn find_mut(plugins: &mut [Box<dyn Plugin>]) -> Option<&mut InputState> {
+        plugins.iter_mut().find_map(|p| p.cast_mut::<Game>()).map(|game| &mut game.input)
+    }
+
+    /// Keyboard input is ignored while a replay runs.
+    pub fn handle_os_event(&mut self, event: &Event<()>) {
+        if let Event::WindowEvent { event: WindowEvent::KeyboardInput { input, .. }, .. } = event {
+            if let Some(action) = input.virtual_keycode.and_then(|k| InputAction::from_key(k, input.state)) {
+                self.push(action);
+            }
+        }
+    }
+
+    /// Queues an action for the next frame, used by the keyboard and by tests.
+    pub fn push(&mut self, action: InputAction) {
+        if self.replay.is_none() {
+            self.pending.push(action);
+        }
+    }
+
+    /// Starts the next frame, its actions are the ones queued since the last call or the ones
+    /// the replay has for it.
+    pub fn begin_frame(&mut self) {
+        self.frame += 1;
+        self.current.clear();
+        match self.replay.as_mut() {
+            Some((replay, cursor)) => {
+                while let Some(input) = replay.inputs.get(*cursor).filter(|i| i.frame <= self.frame) {
+                    self.current.push(input.action);
+                    *cursor += 1;
+                }
+            }
+            None => std::mem::swap(&mut self.current, &mut self.pending),
+        }
+        for action in self.current.iter() {
+            self.recording.inputs.push(RecordedInput { frame: self.frame, action: *action });
+        }
+    }
+
+    pub fn actions(&self) -> &[InputAction] {
+        &self.current
+    }
+
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    /// Starts over from frame zero, recording a new run of the given scene.
+    pub fn restart(&mut self, scene: &str) {
+        self.frame = 0;
+        self.pending.clear();
+        self.current.clear();
+        self.recording = InputRecording {
+            scene: scene.to_string(),
+            inputs: Vec::new(),
+        };
+        if let Some((_, cursor)) = self.replay.as_mut() {
+            *cursor = 0;
+        }
+    }
+
+    /// Plays the recording back from frame zero, call `restart` when its scene starts.
+    pub fn replay(&mut self, recording: InputRecording) {
+        self.replay = Some((recording, 0));
+    }
+
+    pub fn is_replaying(&self) -> bool {
+        self.replay.is_some()
+    }
+
+    pub fn replay_finished(&self) -> bool {
+        self.replay.as_ref().map_or(false, |(replay, cursor)| *cursor >= replay.inputs.len())
+    }
+
+    pub fn recording(&self) -> &InputRecording {
+        &self.recording
+    }
+}
@@ -0,0 +1,66 @@
+use fyrox::{
+    core::{uuid::{Uuid, uuid}, visitor::prelude::*, reflect::prelude::*, TypeUuidProvider},
+    event::Event, impl_component_provider,
+    script::{ScriptContext, ScriptDeinitContext, ScriptTrait},
+};
+use fyrox::core::algebra::Vector3;
+use fyrox::core::pool::Handle;
+use fyrox::scene::node::Node;
+use crate::contacts::{contact_events, ContactPhase};
+use crate::airtime::LaunchKind;
+
+/// Sent to the player to throw them into the air.
+#[derive(Debug, Clone)]
+pub struct LaunchMessage {
+    pub impulse: Vector3<f32>,
+    /// Clears the vertical velocity first so every launch reaches the same height.
+    pub reset_vertical_velocity: bool,
+    pub kind: LaunchKind,
+}
+
+#[derive(Visit, Reflect, Default, Debug, Clone)]
+pub struct JumpPad {
+    pub launch_impulse: Vector3<f32>,
+    pub reset_vertical_velocity: bool,
+    launch_sound: Handle<Node>,
+}
+
+impl_component_provider!(JumpPad);
+
+impl TypeUuidProvider for JumpPad {
+    fn type_uuid() -> Uuid {
+        uuid!("6b2e9d41-0a7c-4f38-b5e2-d19c3f8a4e06")
+    }
+}
+
+impl ScriptTrait for JumpPad {
+    fn on_init(&mut self, _context: &mut ScriptContext) {}
+
+    fn on_start(&mut self, _context: &mut ScriptContext) {}
+
+    fn on_deinit(&mut self, _context: &mut ScriptDeinitContext) {}
+
+    fn on_os_event(&mut self, _event: &Event<()>, _context: &mut ScriptContext) {}
+
+    fn on_update(&mut self, context: &mut ScriptContext) {
+        let graph = &mut context.scene.graph;
+        for event in contact_events(context.plugins, context.handle) {
+            if event.phase != ContactPhase::Enter {
+                continue;
+            }
+            context.message_sender.send_to_target(event.contact.player, LaunchMessage {
+                impulse: self.launch_impulse,
+                reset_vertical_velocity: self.reset_vertical_velocity,
+                kind: LaunchKind::JumpPad,
+            });
+            if let Some(sound) = graph.try_get_mut(self.launch_sound) {
+                sound.as_sound_mut().stop();
+                sound.as_sound_mut().play();
+            }
+        }
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+}
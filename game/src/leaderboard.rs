@@ -0,0 +1,222 @@
+//! Local high-score tables per level, mode and difficulty. The game only talks to
+//! [`LeaderboardStore`], so an online backend can replace the file later.
+use std::path::{Path, PathBuf};
+use fyrox::core::log::Log;
+use serde::{Deserialize, Serialize};
+use strum_macros::AsRefStr;
+use crate::difficulty::Difficulty;
+use crate::profile::SAVE_DIR;
+use crate::time_trial::{Medal, RunResult};
+
+/// Entries kept per table, anything below is dropped.
+pub const TABLE_SIZE: usize = 10;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, AsRefStr)]
+pub enum GameMode {
+    Standard,
+    Daily,
+    Endless,
+}
+
+impl Default for GameMode {
+    fn default() -> Self {
+        Self::Standard
+    }
+}
+
+impl GameMode {
+    pub const ALL: [GameMode; 3] = [GameMode::Standard, GameMode::Daily, GameMode::Endless];
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct LeaderboardEntry {
+    pub name: String,
+    pub level: String,
+    pub mode: GameMode,
+    pub difficulty: Difficulty,
+    pub score: u32,
+    pub time: f32,
+    pub deliveries: usize,
+    /// `YYYY-MM-DD` of the run.
+    pub date: String,
+}
+
+impl LeaderboardEntry {
+    /// Deliveries count the most, then what's left of the packages and the medal.
+    pub fn run_score(result: &RunResult) -> u32 {
+        let medal = match result.medal {
+            Some(Medal::Gold) => 1500,
+            Some(Medal::Silver) => 1000,
+            Some(Medal::Bronze) => 500,
+            None => 0,
+        };
+        result.deliveries as u32 * 1000 + result.package_health * 10 + medal
+    }
+
+    /// Endless runs are scored by the distance covered, a metre per point.
+    pub fn endless_score(distance: f32, deliveries: usize) -> u32 {
+        distance.max(0.0) as u32 + deliveries as u32 * 100
+    }
+
+    /// Daily tables are kept per date, each day plays with different modifiers.
+    fn same_table(&self, other: &LeaderboardEntry) -> bool {
+        self.level == other.level
+            && self.mode == other.mode
+            && self.difficulty == other.difficulty
+            && (self.mode != GameMode::Daily || self.date == other.date)
+    }
+
+    /// Higher score first, the faster run wins a tie. Matches the order entries are kept in.
+    fn ranks_above(&self, other: &LeaderboardEntry) -> bool {
+        self.score > other.score || (self.score == other.score && self.time < other.time)
+    }
+}
+
+/// Which entries to show, `None` matches everything.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct LeaderboardFilter {
+    pub level: Option<String>,
+    pub mode: Option<GameMode>,
+    pub difficulty: Option<Difficulty>,
+}
+
+impl LeaderboardFilter {
+    pub fn matches(&self, entry: &LeaderboardEntry) -> bool {
+        self.level.as_ref().map_or(true, |level| *level == entry.level)
+            && self.mode.map_or(true, |mode| mode == entry.mode)
+            && self.difficulty.map_or(true, |difficulty| difficulty == entry.difficulty)
+    }
+
+    /// Steps through `All` followed by each of `values`.
+    fn cycle<T: Clone + PartialEq>(current: &Option<T>, values: &[T]) -> Option<T> {
+        match current.as_ref().and_then(|c| values.iter().position(|v| v == c)) {
+            None => values.first().cloned(),
+            Some(index) => values.get(index + 1).cloned(),
+        }
+    }
+
+    pub fn next_level(&mut self, levels: &[String]) {
+        self.level = Self::cycle(&self.level, levels);
+    }
+
+    pub fn next_mode(&mut self) {
+        self.mode = Self::cycle(&self.mode, &GameMode::ALL);
+    }
+
+    pub fn next_difficulty(&mut self) {
+        self.difficulty = Self::cycle(
+            &self.difficulty,
+            &[Difficulty::Easy, Difficulty::Normal, Difficulty::Hard, Difficulty::Custom],
+        );
+    }
+}
+
+pub trait LeaderboardStore {
+    /// Adds the entry to its table, returns its rank there or `None` when it didn't make the cut.
+    fn submit(&mut self, entry: LeaderboardEntry) -> Option<usize>;
+
+    /// Matching entries, best first.
+    fn entries(&self, filter: &LeaderboardFilter) -> Vec<LeaderboardEntry>;
+
+    /// Levels that have at least one entry.
+    fn levels(&self) -> Vec<String> {
+        let mut levels = self
+            .entries(&LeaderboardFilter::default())
+            .into_iter()
+            .map(|e| e.level)
+            .collect::<Vec<_>>();
+        levels.sort();
+        levels.dedup();
+        levels
+    }
+}
+
+/// Keeps the tables in memory, used directly by tests and as the cache of [`FileLeaderboard`].
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+#[serde(default)]
+pub struct MemoryLeaderboard {
+    entries: Vec<LeaderboardEntry>,
+}
+
+impl LeaderboardStore for MemoryLeaderboard {
+    fn submit(&mut self, entry: LeaderboardEntry) -> Option<usize> {
+        let rank = self
+            .entries
+            .iter()
+            .filter(|e| e.same_table(&entry))
+            .filter(|e| !entry.ranks_above(e))
+            .count();
+        if rank >= TABLE_SIZE {
+            return None;
+        }
+        self.entries.push(entry.clone());
+        self.entries.sort_by(|a, b| b.score.cmp(&a.score).then(a.time.total_cmp(&b.time)));
+
+        // Drop whatever fell off the bottom of the table.
+        let mut kept = 0;
+        self.entries.retain(|e| {
+            if !e.same_table(&entry) {
+                return true;
+            }
+            kept += 1;
+            kept <= TABLE_SIZE
+        });
+        Some(rank)
+    }
+
+    fn entries(&self, filter: &LeaderboardFilter) -> Vec<LeaderboardEntry> {
+        self.entries.iter().filter(|e| filter.matches(e)).cloned().collect()
+    }
+}
+
+/// Tables stored as RON in the save folder, written after every submission.
+pub struct FileLeaderboard {
+    path: PathBuf,
+    board: MemoryLeaderboard,
+}
+
+impl FileLeaderboard {
+    pub fn default_path() -> PathBuf {
+        Path::new(SAVE_DIR).join("leaderboard.ron")
+    }
+
+    pub fn load(path: &Path) -> Self {
+        let board = match std::fs::read_to_string(path) {
+            Ok(text) => ron::from_str(&text).unwrap_or_else(|err| {
+                Log::err(format!("Unable to parse leaderboard {}: {}", path.display(), err));
+                MemoryLeaderboard::default()
+            }),
+            Err(_) => MemoryLeaderboard::default(),
+        };
+        Self { path: path.to_path_buf(), board }
+    }
+
+    fn save(&self) {
+        let result = ron::ser::to_string_pretty(&self.board, Default::default())
+            .map_err(|err| err.to_string())
+            .and_then(|text| {
+                if let Some(dir) = self.path.parent() {
+                    std::fs::create_dir_all(dir).map_err(|err| err.to_string())?;
+                }
+                std::fs::write(&self.path, text).map_err(|err| err.to_string())
+            });
+        if let Err(err) = result {
+            Log::err(format!("Unable to save leaderboard {}: {}", self.path.display(), err));
+        }
+    }
+}
+
+impl LeaderboardStore for FileLeaderboard {
+    fn submit(&mut self, entry: LeaderboardEntry) -> Option<usize> {
+        let rank = self.board.submit(entry);
+        if rank.is_some() {
+            self.save();
+        }
+        rank
+    }
+
+    fn entries(&self, filter: &LeaderboardFilter) -> Vec<LeaderboardEntry> {
+        self.board.entries(filter)
+    }
+}
@@ -8,26 +8,100 @@ use fyrox::{
     scene::{Scene, loader::AsyncSceneLoader},
     core::log::Log,
 };
+use std::path::Path;
 use fyrox::core::algebra::Vector2;
+use fyrox::event::{ElementState, VirtualKeyCode, WindowEvent};
 use fyrox::engine::GraphicsContext;
 use fyrox::gui::message::MessageDirection;
 use fyrox::gui::text::{TextBuilder, TextMessage};
 use fyrox::gui::UiNode;
 use fyrox::gui::widget::WidgetBuilder;
+use fyrox::asset::manager::ResourceManager;
+use fyrox::asset::Resource;
+use fyrox::scene::rigidbody::RigidBody;
+use crate::achievements::{AchievementList, AchievementTracker};
 use crate::camera_controller::CameraController;
+use crate::jump_pad::JumpPad;
+use crate::leaderboard::{FileLeaderboard, GameMode, LeaderboardEntry, LeaderboardStore};
+use crate::charging_station::ChargingStation;
+use crate::checkpoint::Checkpoint;
+use crate::contacts::ContactService;
+use crate::collectible::Collectible;
+use crate::daily::{DailyChallenge, DailyResult};
+use crate::destructible::Destructible;
+use crate::difficulty::{Difficulty, DifficultyModifiers};
+use crate::endless::EndlessMode;
+use crate::events::{EventBus, GameplayEvent, GameplayObserver};
+use crate::ghost::{Ghost, GhostRace};
+use crate::results::{ResultsAction, ResultsScreen};
+use crate::time_trial::{LevelInfo, LevelTimer, RunResult};
+use crate::input::{InputRecording, InputState, FIXED_TIMESTEP};
+use crate::menu::{Menu, MenuAction};
+use crate::moving_platform::MovingPlatform;
+use crate::one_way_platform::OneWayPlatform;
 use crate::package_pickup_point::PackagePickupPoint;
 use crate::player_controller::PlayerController;
+use crate::profile::Profile;
+use crate::pursuer::Pursuer;
 use crate::reverse_direction::ReverseDirection;
+use crate::scoreboard::ScoreboardScreen;
 use crate::terrain_effect::TerrainEffect;
+use crate::traffic::{TrafficSpawner, TrafficVehicle};
+use crate::trigger::Trigger;
+use crate::terrain_preset::{TerrainPreset, TerrainPresetLoader};
+use crate::tuning::{GameplayTuning, TuningLoader};
 
+pub mod achievements;
+pub mod airtime;
 pub mod camera_controller;
+pub mod charging_station;
+pub mod checkpoint;
+pub mod collectible;
+pub mod contacts;
+pub mod daily;
+pub mod destructible;
+pub mod difficulty;
+pub mod endless;
+pub mod events;
+pub mod ghost;
+pub mod input;
+pub mod jump_pad;
+pub mod leaderboard;
+pub mod menu;
+pub mod moving_platform;
+pub mod one_way_platform;
 pub mod player_controller;
 pub mod terrain_effect;
+pub mod time_trial;
+pub mod terrain_preset;
+pub mod traffic;
+pub mod trigger;
+pub mod tuning;
+pub mod results;
+pub mod scoreboard;
 pub mod reverse_direction;
 pub mod package_pickup_point;
+pub mod package_stack;
+pub mod profile;
+pub mod pursuer;
+
+/// Level the game starts on, and returns to after the daily challenge.
+const DEFAULT_SCENE: &str = "data/scene.rgs";
+const ENDLESS_SCENE: &str = "data/endless.rgs";
 
 pub struct GameConstructor;
 
+impl GameConstructor {
+    /// Teaches the resource manager the game's own file types. The registration context has no
+    /// resource manager, so executors and the editor call this before any scene is loaded.
+    pub fn register_resources(resource_manager: &ResourceManager) {
+        let mut state = resource_manager.state();
+        state.constructors_container.add::<TerrainPreset>();
+        state.loaders.set(TerrainPresetLoader);
+        state.loaders.set(TuningLoader);
+    }
+}
+
 impl PluginConstructor for GameConstructor {
     fn register(&self, context: PluginRegistrationContext) {
         context.serialization_context.script_constructors.add::<CameraController>("Camera Controller");
@@ -35,6 +109,20 @@ impl PluginConstructor for GameConstructor {
         context.serialization_context.script_constructors.add::<TerrainEffect>("Terrain Effects");
         context.serialization_context.script_constructors.add::<ReverseDirection>("Reverse Direction");
         context.serialization_context.script_constructors.add::<PackagePickupPoint>("Package Pickup Point");
+        context.serialization_context.script_constructors.add::<ChargingStation>("Charging Station");
+        context.serialization_context.script_constructors.add::<Checkpoint>("Checkpoint");
+        context.serialization_context.script_constructors.add::<Collectible>("Collectible");
+        context.serialization_context.script_constructors.add::<MovingPlatform>("Moving Platform");
+        context.serialization_context.script_constructors.add::<OneWayPlatform>("One Way Platform");
+        context.serialization_context.script_constructors.add::<JumpPad>("Jump Pad");
+        context.serialization_context.script_constructors.add::<Destructible>("Destructible");
+        context.serialization_context.script_constructors.add::<TrafficVehicle>("Traffic Vehicle");
+        context.serialization_context.script_constructors.add::<TrafficSpawner>("Traffic Spawner");
+        context.serialization_context.script_constructors.add::<Pursuer>("Pursuer");
+        context.serialization_context.script_constructors.add::<Trigger>("Trigger");
+        context.serialization_context.script_constructors.add::<Ghost>("Ghost");
+        context.serialization_context.script_constructors.add::<LevelInfo>("Level Info");
+        context.serialization_context.script_constructors.add::<EndlessMode>("Endless Mode");
     }
 
     fn create_instance(
@@ -51,16 +139,64 @@ pub struct Game {
     loader: Option<AsyncSceneLoader>,
     health_ui: Handle<UiNode>,
     package_ui: Handle<UiNode>,
+    battery_ui: Handle<UiNode>,
+    power_up_ui: Handle<UiNode>,
+    airtime_ui: Handle<UiNode>,
+    pursuer_ui: Handle<UiNode>,
+    trigger_ui: Handle<UiNode>,
+    ghost_ui: Handle<UiNode>,
+    timer_ui: Handle<UiNode>,
+    toast_ui: Handle<UiNode>,
+    /// Seconds the achievement toast stays up for.
+    toast_timer: f32,
+    tuning: Resource<GameplayTuning>,
+    /// Last tuning pushed into the scene, reapplied when a new scene loads.
+    applied_tuning: Option<GameplayTuning>,
+    profile: Profile,
+    menu: Menu,
+    contacts: ContactService,
+    events: EventBus,
+    input: InputState,
+    scene_path: String,
+    ghost: GhostRace,
+    level_completed: bool,
+    timer: LevelTimer,
+    /// Deliveries this run, with the health left in the packages and what they started with.
+    deliveries: (usize, u32, u32),
+    results: ResultsScreen,
+    daily: Option<DailyChallenge>,
+    /// Only the first attempt of the day counts towards the daily result.
+    daily_scored: bool,
+    leaderboard: Box<dyn LeaderboardStore>,
+    scoreboard: ScoreboardScreen,
+    /// Entry for the finished run, waiting for a name on the results screen.
+    pending_score: Option<LeaderboardEntry>,
+    achievements: AchievementTracker,
 }
 
 impl Game {
     pub fn new(override_scene: Handle<Scene>, context: PluginContext) -> Self {
+        // `--replay <file>` plays back a recorded run, e.g. one sent in with a bug report.
+        let mut input = InputState::default();
+        let mut scene_path = DEFAULT_SCENE.to_string();
+        let args = std::env::args().collect::<Vec<_>>();
+        if let Some(path) = args.iter().position(|a| a == "--replay").and_then(|i| args.get(i + 1)) {
+            if let Some(recording) = InputRecording::load(path.as_ref()) {
+                if !recording.scene.is_empty() {
+                    scene_path = recording.scene.clone();
+                }
+                input.replay(recording);
+            }
+        }
+
         let mut loader = None;
         let scene = if override_scene.is_some() {
+            input.restart("");
+            Self::use_fixed_timestep(&mut context.scenes[override_scene]);
             override_scene
         } else {
             loader = Some(AsyncSceneLoader::begin_loading(
-                "data/scene.rgs".into(),
+                scene_path.clone().into(),
                 context.serialization_context.clone(),
                 context.resource_manager.clone(),
             ));
@@ -74,13 +210,368 @@ impl Game {
             .with_desired_position(Vector2::new(10.0, 25.0)))
             .build(&mut context.user_interface.build_ctx());
 
-        Self { scene, loader, health_ui: health_text, package_ui: package_text }
+        let battery_text = TextBuilder::new(WidgetBuilder::new()
+            .with_desired_position(Vector2::new(10.0, 40.0)))
+            .build(&mut context.user_interface.build_ctx());
+
+        let power_up_text = TextBuilder::new(WidgetBuilder::new()
+            .with_desired_position(Vector2::new(10.0, 55.0)))
+            .build(&mut context.user_interface.build_ctx());
+
+        let airtime_text = TextBuilder::new(WidgetBuilder::new()
+            .with_desired_position(Vector2::new(10.0, 70.0)))
+            .build(&mut context.user_interface.build_ctx());
+
+        let pursuer_text = TextBuilder::new(WidgetBuilder::new()
+            .with_desired_position(Vector2::new(10.0, 85.0)))
+            .build(&mut context.user_interface.build_ctx());
+
+        let trigger_text = TextBuilder::new(WidgetBuilder::new()
+            .with_desired_position(Vector2::new(10.0, 100.0)))
+            .build(&mut context.user_interface.build_ctx());
+
+        let ghost_text = TextBuilder::new(WidgetBuilder::new()
+            .with_desired_position(Vector2::new(10.0, 115.0)))
+            .build(&mut context.user_interface.build_ctx());
+
+        let timer_text = TextBuilder::new(WidgetBuilder::new()
+            .with_desired_position(Vector2::new(10.0, 130.0)))
+            .build(&mut context.user_interface.build_ctx());
+
+        let toast_text = TextBuilder::new(WidgetBuilder::new()
+            .with_desired_position(Vector2::new(10.0, 145.0)))
+            .build(&mut context.user_interface.build_ctx());
+
+        let profile = Profile::load(&Profile::default_path());
+        let achievements = AchievementTracker::new(
+            AchievementList::load("data/achievements.ron"),
+            profile.achievements.clone(),
+        );
+        let menu = Menu::new(&mut context.user_interface.build_ctx());
+        menu.set_status(format!("Difficulty: {}", profile.difficulty.as_ref()), context.user_interface);
+        menu.show_custom(&profile.custom_difficulty, context.user_interface);
+
+        Self {
+            scene,
+            loader,
+            health_ui: health_text,
+            package_ui: package_text,
+            battery_ui: battery_text,
+            power_up_ui: power_up_text,
+            airtime_ui: airtime_text,
+            pursuer_ui: pursuer_text,
+            trigger_ui: trigger_text,
+            ghost_ui: ghost_text,
+            timer_ui: timer_text,
+            toast_ui: toast_text,
+            toast_timer: 0.0,
+            tuning: context.resource_manager.request::<GameplayTuning, _>(GameplayTuning::PATH),
+            applied_tuning: None,
+            profile,
+            menu,
+            contacts: Default::default(),
+            events: Default::default(),
+            input,
+            scene_path,
+            ghost: Default::default(),
+            level_completed: false,
+            timer: Default::default(),
+            deliveries: (0, 0, 0),
+            results: ResultsScreen::new(&mut context.user_interface.build_ctx()),
+            daily: None,
+            daily_scored: false,
+            leaderboard: Box::new(FileLeaderboard::load(&FileLeaderboard::default_path())),
+            scoreboard: ScoreboardScreen::new(&mut context.user_interface.build_ctx()),
+            pending_score: None,
+            achievements,
+        }
+    }
+
+    /// Resets everything that belongs to one attempt at the level.
+    fn start_run(&mut self) {
+        self.input.restart(&self.scene_path);
+        if self.daily.is_some() {
+            // Daily runs are modified, racing or saving the level's ghost would mix them up.
+            self.ghost = Default::default();
+        } else {
+            self.ghost.start(&Self::level_name(&self.scene_path), FIXED_TIMESTEP);
+        }
+        self.level_completed = false;
+        self.timer = Default::default();
+        self.deliveries = (0, 0, 0);
+        self.pending_score = None;
+        self.achievements.set_run(&Self::level_name(&self.scene_path), self.run_difficulty());
+    }
+
+    /// Loads the level again from its file.
+    fn reload(&mut self, context: &mut PluginContext) {
+        if context.scenes.try_get(self.scene).is_some() {
+            context.scenes.remove(self.scene);
+        }
+        self.scene = Handle::NONE;
+        self.loader = Some(AsyncSceneLoader::begin_loading(
+            self.scene_path.clone().into(),
+            context.serialization_context.clone(),
+            context.resource_manager.clone(),
+        ));
+    }
+
+    /// Stops the clock and shows how the run went.
+    fn complete_level(&mut self, context: &mut PluginContext) {
+        let mode = self.leaderboard_mode(context);
+        self.timer.stop();
+        self.ghost.finish();
+        let level = context.scenes.try_get(self.scene)
+            .and_then(|scene| scene.graph.linear_iter().find_map(|n| n.try_get_script::<LevelInfo>()).cloned())
+            .unwrap_or_default();
+        let (deliveries, health, max_health) = self.deliveries;
+        let result = RunResult::new(self.timer.time(), deliveries, health, max_health, &level);
+        self.profile.achievements = self.achievements.progress().clone();
+        // Daily runs go to the daily results only, the level's records are for unmodified runs.
+        let mut new_best = false;
+        match self.daily.as_ref() {
+            Some(daily) if self.daily_scored => {
+                self.profile.daily.insert(daily.date.clone(), DailyResult {
+                    level: daily.level.clone(),
+                    time: Some(result.final_time()),
+                    deliveries,
+                    medal: result.medal,
+                });
+                self.daily_scored = false;
+                self.menu.set_status(format!("{} - scored", daily.description()), context.user_interface);
+            }
+            Some(_) => (),
+            None => {
+                new_best = self.profile.records
+                    .entry(Self::level_name(&self.scene_path))
+                    .or_default()
+                    .submit(&result);
+            }
+        }
+        self.profile.save(&Profile::default_path());
+        self.results.show(&result, new_best, context.user_interface);
+        self.offer_score(mode, LeaderboardEntry::run_score(&result), result.final_time(), deliveries, context);
+    }
+
+    /// An endless run is over once the player dies, scored by how far they got.
+    fn end_endless_run(&mut self, distance: f32, context: &mut PluginContext) {
+        self.level_completed = true;
+        self.timer.stop();
+        let (deliveries, health, max_health) = self.deliveries;
+        let result = RunResult {
+            time: self.timer.time(),
+            deliveries,
+            package_health: health,
+            max_package_health: max_health,
+            ..Default::default()
+        };
+        self.results.show(&result, false, context.user_interface);
+        let mode = self.leaderboard_mode(context);
+        self.offer_score(mode, LeaderboardEntry::endless_score(distance, deliveries), result.time, deliveries, context);
+    }
+
+    /// Table the current run goes on, none for daily practice runs after the scored attempt.
+    fn leaderboard_mode(&self, context: &PluginContext) -> Option<GameMode> {
+        if self.daily.is_some() {
+            return self.daily_scored.then_some(GameMode::Daily);
+        }
+        let is_endless = context.scenes.try_get(self.scene)
+            .map_or(false, |scene| scene.graph.linear_iter().any(|n| n.has_script::<EndlessMode>()));
+        Some(if is_endless { GameMode::Endless } else { GameMode::Standard })
+    }
+
+    fn offer_score(&mut self, mode: Option<GameMode>, score: u32, time: f32, deliveries: usize, context: &mut PluginContext) {
+        let mode = match mode {
+            Some(mode) => mode,
+            None => {
+                self.results.hide_score_entry(context.user_interface);
+                return;
+            }
+        };
+        self.pending_score = Some(LeaderboardEntry {
+            name: Default::default(),
+            level: Self::level_name(&self.scene_path),
+            mode,
+            difficulty: self.run_difficulty(),
+            score,
+            time,
+            deliveries,
+            // Daily tables go by the challenge's date, even if the run ends after midnight.
+            date: self.daily.as_ref().map_or_else(daily::today, |daily| daily.date.clone()),
+        });
+        self.results.show_score_entry(&self.profile.player_name, context.user_interface);
+    }
+
+    fn save_achievements(&mut self) {
+        self.profile.achievements = self.achievements.progress().clone();
+        self.profile.save(&Profile::default_path());
+    }
+
+    /// Switches to today's challenge level. The attempt is recorded as soon as it starts so
+    /// quitting and retrying doesn't give a second scored run.
+    fn start_daily(&mut self, context: &mut PluginContext) {
+        let daily = DailyChallenge::today();
+        self.daily_scored = !self.profile.daily.contains_key(&daily.date);
+        if self.daily_scored {
+            self.profile.daily.insert(daily.date.clone(), DailyResult {
+                level: daily.level.clone(),
+                ..Default::default()
+            });
+            self.profile.save(&Profile::default_path());
+        }
+        let status = if self.daily_scored { "scored" } else { "practice" };
+        self.menu.set_status(format!("{} - {}", daily.description(), status), context.user_interface);
+        self.scene_path = daily.level.clone();
+        self.daily = Some(daily);
+        self.menu.set_visible(false, context.user_interface);
+        self.reload(context);
+    }
+
+    /// Goes back to the regular level with the player's own difficulty.
+    fn leave_daily(&mut self, context: &mut PluginContext) {
+        if self.daily.take().is_none() {
+            return;
+        }
+        self.daily_scored = false;
+        self.scene_path = DEFAULT_SCENE.to_string();
+        self.menu.set_status(format!("Difficulty: {}", self.profile.difficulty.as_ref()), context.user_interface);
+        self.menu.set_visible(false, context.user_interface);
+        self.reload(context);
+    }
+
+    /// Switches to the generated endless track, with the player's own difficulty.
+    fn start_endless(&mut self, context: &mut PluginContext) {
+        self.daily = None;
+        self.daily_scored = false;
+        self.scene_path = ENDLESS_SCENE.to_string();
+        self.menu.set_status(format!("Difficulty: {}", self.profile.difficulty.as_ref()), context.user_interface);
+        self.menu.set_visible(false, context.user_interface);
+        self.reload(context);
+    }
+
+    /// The profile's difficulty, or the daily challenge's modifiers on top of Normal so every
+    /// player gets the same run that day.
+    fn difficulty_modifiers(&self) -> DifficultyModifiers {
+        match self.daily.as_ref() {
+            Some(daily) => {
+                let mut modifiers = Difficulty::Normal.modifiers(&DifficultyModifiers::default());
+                daily.apply(&mut modifiers);
+                modifiers
+            }
+            None => self.profile.difficulty_modifiers(),
+        }
+    }
+
+    /// Difficulty the current run counts as, for leaderboards and achievements.
+    fn run_difficulty(&self) -> Difficulty {
+        if self.daily.is_some() {
+            Difficulty::Normal
+        } else {
+            self.profile.difficulty
+        }
+    }
+
+    /// Name used for the level's save files, the scene file name without extension.
+    fn level_name(scene_path: &str) -> String {
+        Path::new(scene_path)
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    }
+
+    /// The level is done once every drop-off has been delivered to.
+    fn is_level_completed(scene: &Scene) -> bool {
+        let mut drop_offs = scene.graph.linear_iter()
+            .filter_map(|n| n.try_get_script::<PackagePickupPoint>())
+            .filter(|p| p.is_drop_off)
+            .peekable();
+        drop_offs.peek().is_some() && drop_offs.all(|p| p.is_delivered())
+    }
+
+    /// Samples the player for the ghost race and moves the ghost bike to the best run's position.
+    fn update_ghost(&mut self, scene: &mut Scene, dt: f32) {
+        let player = scene.graph.find_from_root(&mut |n| n.has_script::<PlayerController>())
+            .map(|(handle, node)| (handle, node.try_get_script::<PlayerController>().unwrap().model()));
+        if let Some((player, model)) = player.filter(|_| self.timer.is_running()) {
+            let mut rotation = **scene.graph[player].local_transform().rotation();
+            if let Some(model) = scene.graph.try_get(model) {
+                rotation *= **model.local_transform().rotation();
+            }
+            let position = scene.graph[player].global_position();
+            self.ghost.update(dt, position, rotation);
+        }
+
+        let transform = self.ghost.ghost_transform();
+        for node in scene.graph.linear_iter_mut() {
+            let offset = match node.try_get_script::<Ghost>() {
+                Some(ghost) => ghost.offset,
+                None => continue,
+            };
+            node.set_visibility(transform.is_some());
+            if let Some((position, rotation)) = transform {
+                node.local_transform_mut()
+                    .set_position(position + offset)
+                    .set_rotation(rotation);
+            }
+        }
+    }
+
+    /// Steps the physics by the same amount every update so recorded runs replay exactly.
+    fn use_fixed_timestep(scene: &mut Scene) {
+        scene.graph.physics.integration_parameters.dt = Some(FIXED_TIMESTEP);
+    }
+
+    /// Gives a player spawned mid-run the difficulty and tuning the level's players got on load.
+    pub(crate) fn prepare_player(&self, player: &mut PlayerController) {
+        player.apply_difficulty(self.difficulty_modifiers());
+        if let Some(tuning) = self.applied_tuning.as_ref() {
+            player.apply_tuning(tuning);
+        }
+    }
+
+    fn apply_difficulty(scene: &mut Scene, difficulty: &DifficultyModifiers) {
+        for node in scene.graph.linear_iter_mut() {
+            if let Some(player) = node.try_get_script_mut::<PlayerController>() {
+                player.apply_difficulty(difficulty.clone());
+            }
+        }
+    }
+
+    /// Seeds everything random in the level so a daily challenge plays the same for everyone.
+    /// Each node gets its own seed so identical prefabs don't move in lockstep.
+    fn apply_seed(scene: &mut Scene, seed: u64) {
+        for (handle, node) in scene.graph.pair_iter_mut() {
+            let seed = seed ^ (handle.index() as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+            if let Some(endless) = node.try_get_script_mut::<EndlessMode>() {
+                endless.seed = seed;
+            }
+            if let Some(spawner) = node.try_get_script_mut::<TrafficSpawner>() {
+                spawner.seed = seed;
+            }
+            if let Some(destructible) = node.try_get_script_mut::<Destructible>() {
+                destructible.seed = seed;
+            }
+            if let Some(camera) = node.try_get_script_mut::<CameraController>() {
+                camera.seed = seed;
+            }
+        }
+    }
+
+    fn apply_tuning(scene: &mut Scene, tuning: &GameplayTuning) {
+        for node in scene.graph.linear_iter_mut() {
+            if let Some(player) = node.try_get_script_mut::<PlayerController>() {
+                player.apply_tuning(tuning);
+            }
+        }
     }
 }
 
 impl Plugin for Game {
     fn on_deinit(&mut self, _context: PluginContext) {
-        // Do a cleanup here.
+        self.save_achievements();
+        if !self.input.is_replaying() && !self.input.recording().scene.is_empty() {
+            self.input.recording().save(&InputRecording::last_run_path());
+        }
     }
 
     fn update(&mut self, context: &mut PluginContext, _control_flow: &mut ControlFlow) {
@@ -89,6 +580,15 @@ impl Plugin for Game {
                 match result {
                     Ok(scene) => {
                         self.scene = context.scenes.add(scene);
+                        self.start_run();
+                        Self::use_fixed_timestep(&mut context.scenes[self.scene]);
+                        if let Some(tuning) = self.applied_tuning.as_ref() {
+                            Self::apply_tuning(&mut context.scenes[self.scene], tuning);
+                        }
+                        if let Some(daily) = self.daily.as_ref() {
+                            Self::apply_seed(&mut context.scenes[self.scene], daily.seed);
+                        }
+                        Self::apply_difficulty(&mut context.scenes[self.scene], &self.difficulty_modifiers());
                     }
                     Err(err) => Log::err(err),
                 }
@@ -97,6 +597,84 @@ impl Plugin for Game {
 
         // Add your global update code here.
 
+        self.input.begin_frame();
+
+        self.events.dispatch();
+        let mut completed = false;
+        let mut died = false;
+        for event in self.events.delivered() {
+            match event {
+                GameplayEvent::Checkpoint { .. } => self.ghost.checkpoint(),
+                GameplayEvent::Delivery { count, health, max_health, .. } => {
+                    self.deliveries.0 += count;
+                    self.deliveries.1 += health;
+                    self.deliveries.2 += max_health;
+                }
+                GameplayEvent::LevelCompleted => completed = true,
+                GameplayEvent::Death { .. } => died = true,
+                _ => (),
+            }
+            self.achievements.on_event(event);
+        }
+        let unlocked = self.achievements.take_unlocked();
+        if !unlocked.is_empty() {
+            self.save_achievements();
+            context.user_interface.send_message(TextMessage::text(
+                self.toast_ui,
+                MessageDirection::ToWidget,
+                unlocked.iter().map(|name| format!("Achievement unlocked: {}", name)).collect::<Vec<_>>().join("\n"),
+            ));
+            self.toast_timer = 4.0;
+        }
+        if self.toast_timer > 0.0 {
+            self.toast_timer -= context.dt;
+            if self.toast_timer <= 0.0 {
+                context.user_interface.send_message(TextMessage::text(self.toast_ui, MessageDirection::ToWidget, String::new()));
+            }
+        }
+        if completed {
+            self.complete_level(context);
+        }
+        if died && !self.level_completed {
+            let endless = context.scenes.try_get(self.scene).and_then(|scene| {
+                let graph = &scene.graph;
+                graph.linear_iter()
+                    .filter_map(|n| n.try_get_script::<EndlessMode>())
+                    .find_map(|e| e.distance(graph))
+            });
+            if let Some(distance) = endless {
+                self.end_endless_run(distance, context);
+            }
+        }
+
+        if let Some(scene) = context.scenes.try_get_mut(self.scene) {
+            self.contacts.update(&scene.graph);
+            if !self.level_completed && Self::is_level_completed(scene) {
+                self.level_completed = true;
+                self.events.publish(GameplayEvent::LevelCompleted);
+            }
+            let moving = !self.input.actions().is_empty() || scene.graph.linear_iter()
+                .filter(|n| n.has_script::<PlayerController>())
+                .filter_map(|n| n.cast::<RigidBody>())
+                .any(|b| b.lin_vel().norm() > 0.1);
+            self.timer.update(context.dt, moving);
+            self.update_ghost(scene, context.dt);
+        }
+
+        if self.tuning.is_ok() {
+            let tuning = self.tuning.data_ref().clone();
+            if self.applied_tuning.as_ref() != Some(&tuning) {
+                if let Some(scene) = context.scenes.try_get_mut(self.scene) {
+                    Self::apply_tuning(scene, &tuning);
+                }
+                self.applied_tuning = Some(tuning);
+            }
+        }
+
+        if context.scenes.try_get(self.scene).is_none() {
+            return;
+        }
+
         if let GraphicsContext::Initialized(ref graphics_context) = context.graphics_context {
             let player = context.scenes[self.scene].graph.find_from_root(&mut |n| n.has_script::<PlayerController>()).unwrap().1.script().unwrap().cast::<PlayerController>().unwrap();
             context.user_interface.send_message(TextMessage::text(
@@ -107,26 +685,158 @@ impl Plugin for Game {
             context.user_interface.send_message(TextMessage::text(
                 self.package_ui,
                 MessageDirection::ToWidget,
-                format!("Package health: {}", player.actual_package_health)
+                format!("Packages {}/{}: {}",
+                        player.cargo.len(),
+                        player.cargo.capacity,
+                        player.cargo.packages().iter()
+                            .map(|p| match p.deadline {
+                                Some(deadline) => format!("{} ({:.0}s)", p.health, deadline.ceil()),
+                                None => p.health.to_string(),
+                            })
+                            .collect::<Vec<_>>()
+                            .join(" "))
+            ));
+            context.user_interface.send_message(TextMessage::text(
+                self.battery_ui,
+                MessageDirection::ToWidget,
+                format!("Battery: {:.0}%", 100.0 * player.actual_battery_charge / player.battery_capacity.max(f32::EPSILON))
+            ));
+            context.user_interface.send_message(TextMessage::text(
+                self.power_up_ui,
+                MessageDirection::ToWidget,
+                player.power_ups.active().iter()
+                    .map(|p| format!("{} {:.1}s", p.effect.as_ref(), p.remaining))
+                    .collect::<Vec<_>>()
+                    .join("  ")
+            ));
+            let stats = &player.airtime.stats;
+            context.user_interface.send_message(TextMessage::text(
+                self.airtime_ui,
+                MessageDirection::ToWidget,
+                format!("Air score: {}  big airs: {}  flips: {}  best: {:.1}s",
+                        stats.score, stats.big_airs, stats.flips, stats.best_air_time)
+            ));
+            let pursuer = context.scenes[self.scene].graph.linear_iter()
+                .filter_map(|n| n.try_get_script::<Pursuer>())
+                .filter_map(|p| p.distance())
+                .reduce(f32::min);
+            context.user_interface.send_message(TextMessage::text(
+                self.pursuer_ui,
+                MessageDirection::ToWidget,
+                pursuer.map(|d| format!("Pursuer: {:.1}m behind", d)).unwrap_or_default()
+            ));
+            context.user_interface.send_message(TextMessage::text(
+                self.ghost_ui,
+                MessageDirection::ToWidget,
+                self.ghost.delta()
+                    .map(|d| format!("Ghost: {}{:.2}s", if d > 0.0 { "+" } else { "" }, d))
+                    .unwrap_or_default()
+            ));
+            let graph = &context.scenes[self.scene].graph;
+            let endless = graph.linear_iter()
+                .filter_map(|n| n.try_get_script::<EndlessMode>())
+                .find_map(|e| e.distance(graph));
+            context.user_interface.send_message(TextMessage::text(
+                self.timer_ui,
+                MessageDirection::ToWidget,
+                match endless {
+                    Some(distance) => format!("Time: {:.2}s  Distance: {:.0}m", self.timer.time(), distance.max(0.0)),
+                    None => format!("Time: {:.2}s", self.timer.time()),
+                }
+            ));
+            let mut trigger_lines = Vec::new();
+            for trigger in context.scenes[self.scene].graph.linear_iter().filter_map(|n| n.try_get_script::<Trigger>()) {
+                if let Some((label, remaining)) = trigger.countdown() {
+                    trigger_lines.push(format!("{} {:.1}s", label, remaining.max(0.0)));
+                }
+                if let Some(message) = trigger.message() {
+                    trigger_lines.push(message.to_string());
+                }
+            }
+            context.user_interface.send_message(TextMessage::text(
+                self.trigger_ui,
+                MessageDirection::ToWidget,
+                trigger_lines.join("  ")
             ));
         }
     }
 
     fn on_os_event(
         &mut self,
-        _event: &Event<()>,
-        _context: PluginContext,
+        event: &Event<()>,
+        context: PluginContext,
         _control_flow: &mut ControlFlow,
     ) {
-        // Do something on OS event here.
+        self.input.handle_os_event(event);
+        if let Event::WindowEvent { event: WindowEvent::KeyboardInput { input, .. }, .. } = event {
+            if input.state == ElementState::Pressed && input.virtual_keycode == Some(VirtualKeyCode::Escape) {
+                self.menu.set_visible(!self.menu.is_visible(), context.user_interface);
+            }
+        }
     }
 
     fn on_ui_message(
         &mut self,
-        _context: &mut PluginContext,
-        _message: &UiMessage,
+        context: &mut PluginContext,
+        message: &UiMessage,
         _control_flow: &mut ControlFlow,
     ) {
-        // Handle UI events here.
+        match self.menu.handle_message(message) {
+            Some(MenuAction::Resume) => self.menu.set_visible(false, context.user_interface),
+            Some(MenuAction::SelectDifficulty(_)) if self.daily.is_some() => {
+                self.menu.set_status("Difficulty is fixed during the daily challenge".to_string(), context.user_interface);
+            }
+            Some(MenuAction::SelectDifficulty(difficulty)) => {
+                self.profile.difficulty = difficulty;
+                self.achievements.change_difficulty(difficulty);
+                self.profile.save(&Profile::default_path());
+                self.menu.set_status(format!("Difficulty: {}", difficulty.as_ref()), context.user_interface);
+                if let Some(scene) = context.scenes.try_get_mut(self.scene) {
+                    Self::apply_difficulty(scene, &self.difficulty_modifiers());
+                }
+            }
+            Some(MenuAction::AdjustCustom(..)) if self.daily.is_some() => {
+                self.menu.set_status("Difficulty is fixed during the daily challenge".to_string(), context.user_interface);
+            }
+            Some(MenuAction::AdjustCustom(setting, delta)) => {
+                setting.adjust(&mut self.profile.custom_difficulty, delta);
+                self.profile.save(&Profile::default_path());
+                self.menu.show_custom(&self.profile.custom_difficulty, context.user_interface);
+                if self.profile.difficulty == Difficulty::Custom {
+                    if let Some(scene) = context.scenes.try_get_mut(self.scene) {
+                        Self::apply_difficulty(scene, &self.difficulty_modifiers());
+                    }
+                }
+            }
+            Some(MenuAction::DailyChallenge) => self.start_daily(context),
+            Some(MenuAction::LeaveDaily) => self.leave_daily(context),
+            Some(MenuAction::Endless) => self.start_endless(context),
+            Some(MenuAction::Leaderboard) => {
+                self.menu.set_visible(false, context.user_interface);
+                self.scoreboard.show(self.leaderboard.as_ref(), context.user_interface);
+            }
+            None => (),
+        }
+        if self.scoreboard.handle_message(message, self.leaderboard.as_ref(), context.user_interface) {
+            self.menu.set_visible(true, context.user_interface);
+        }
+        match self.results.handle_message(message) {
+            Some(ResultsAction::Retry) => {
+                self.results.set_visible(false, context.user_interface);
+                self.daily_scored = false;
+                self.reload(context);
+            }
+            Some(ResultsAction::Close) => self.results.set_visible(false, context.user_interface),
+            Some(ResultsAction::SubmitScore(name)) => {
+                if let Some(mut entry) = self.pending_score.take() {
+                    entry.name = name.clone();
+                    let rank = self.leaderboard.submit(entry);
+                    self.results.show_rank(rank, context.user_interface);
+                    self.profile.player_name = name;
+                    self.profile.save(&Profile::default_path());
+                }
+            }
+            None => (),
+        }
     }
 }
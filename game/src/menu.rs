@@ -0,0 +1,144 @@
+use fyrox::core::pool::Handle;
+use fyrox::gui::button::{ButtonBuilder, ButtonMessage};
+use fyrox::gui::message::{MessageDirection, UiMessage};
+use fyrox::gui::stack_panel::StackPanelBuilder;
+use fyrox::gui::text::{TextBuilder, TextMessage};
+use fyrox::gui::widget::{WidgetBuilder, WidgetMessage};
+use fyrox::gui::{BuildContext, HorizontalAlignment, Orientation, Thickness, UiNode, UserInterface, VerticalAlignment};
+use crate::difficulty::{CustomSetting, Difficulty, DifficultyModifiers};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MenuAction {
+    Resume,
+    SelectDifficulty(Difficulty),
+    AdjustCustom(CustomSetting, f32),
+    DailyChallenge,
+    LeaveDaily,
+    Endless,
+    Leaderboard,
+}
+
+/// Pause menu toggled with Escape.
+pub struct Menu {
+    root: Handle<UiNode>,
+    status: Handle<UiNode>,
+    buttons: Vec<(Handle<UiNode>, MenuAction)>,
+    custom_values: Vec<(CustomSetting, Handle<UiNode>)>,
+    visible: bool,
+}
+
+fn make_button(ctx: &mut BuildContext, text: &str) -> Handle<UiNode> {
+    ButtonBuilder::new(WidgetBuilder::new()
+        .with_width(200.0)
+        .with_height(30.0)
+        .with_margin(Thickness::uniform(2.0)))
+        .with_text(text)
+        .build(ctx)
+}
+
+fn make_small_button(ctx: &mut BuildContext, text: &str) -> Handle<UiNode> {
+    ButtonBuilder::new(WidgetBuilder::new()
+        .with_width(30.0)
+        .with_height(30.0)
+        .with_margin(Thickness::uniform(2.0)))
+        .with_text(text)
+        .build(ctx)
+}
+
+impl Menu {
+    pub fn new(ctx: &mut BuildContext) -> Self {
+        let status = TextBuilder::new(WidgetBuilder::new()
+            .with_margin(Thickness::uniform(2.0)))
+            .build(ctx);
+
+        let mut buttons = vec![(make_button(ctx, "Resume"), MenuAction::Resume)];
+        for difficulty in [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard, Difficulty::Custom] {
+            let button = make_button(ctx, difficulty.as_ref());
+            buttons.push((button, MenuAction::SelectDifficulty(difficulty)));
+        }
+
+        // One row per custom difficulty value: label, -, value, +.
+        let mut custom_values = Vec::new();
+        let mut custom_rows = Vec::new();
+        for setting in CustomSetting::ALL {
+            let label = TextBuilder::new(WidgetBuilder::new()
+                .with_width(120.0)
+                .with_vertical_alignment(VerticalAlignment::Center))
+                .with_text(setting.label())
+                .build(ctx);
+            let minus = make_small_button(ctx, "-");
+            let value = TextBuilder::new(WidgetBuilder::new()
+                .with_width(40.0)
+                .with_vertical_alignment(VerticalAlignment::Center))
+                .build(ctx);
+            let plus = make_small_button(ctx, "+");
+            buttons.push((minus, MenuAction::AdjustCustom(setting, -CustomSetting::STEP)));
+            buttons.push((plus, MenuAction::AdjustCustom(setting, CustomSetting::STEP)));
+            custom_values.push((setting, value));
+            custom_rows.push(StackPanelBuilder::new(WidgetBuilder::new()
+                .with_child(label)
+                .with_child(minus)
+                .with_child(value)
+                .with_child(plus))
+                .with_orientation(Orientation::Horizontal)
+                .build(ctx));
+        }
+
+        buttons.push((make_button(ctx, "Daily Challenge"), MenuAction::DailyChallenge));
+        buttons.push((make_button(ctx, "Leave Daily Challenge"), MenuAction::LeaveDaily));
+        buttons.push((make_button(ctx, "Endless Run"), MenuAction::Endless));
+        buttons.push((make_button(ctx, "Leaderboard"), MenuAction::Leaderboard));
+
+        let mut panel = WidgetBuilder::new()
+            .with_visibility(false)
+            .with_horizontal_alignment(HorizontalAlignment::Center)
+            .with_vertical_alignment(VerticalAlignment::Center)
+            .with_child(status);
+        for (button, action) in buttons.iter() {
+            // The custom rows' buttons are already inside their rows, which go under the Custom button.
+            if matches!(action, MenuAction::AdjustCustom(..)) {
+                continue;
+            }
+            panel = panel.with_child(*button);
+            if *action == MenuAction::SelectDifficulty(Difficulty::Custom) {
+                for row in custom_rows.drain(..) {
+                    panel = panel.with_child(row);
+                }
+            }
+        }
+        let root = StackPanelBuilder::new(panel).build(ctx);
+
+        Self { root, status, buttons, custom_values, visible: false }
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    pub fn set_visible(&mut self, visible: bool, ui: &UserInterface) {
+        self.visible = visible;
+        ui.send_message(WidgetMessage::visibility(self.root, MessageDirection::ToWidget, visible));
+    }
+
+    pub fn set_status(&self, text: String, ui: &UserInterface) {
+        ui.send_message(TextMessage::text(self.status, MessageDirection::ToWidget, text));
+    }
+
+    /// Shows the current custom difficulty values next to their buttons.
+    pub fn show_custom(&self, custom: &DifficultyModifiers, ui: &UserInterface) {
+        for (setting, text) in self.custom_values.iter() {
+            ui.send_message(TextMessage::text(*text, MessageDirection::ToWidget, format!("{:.2}", setting.get(custom))));
+        }
+    }
+
+    pub fn handle_message(&self, message: &UiMessage) -> Option<MenuAction> {
+        if let Some(ButtonMessage::Click) = message.data() {
+            return self
+                .buttons
+                .iter()
+                .find(|(button, _)| *button == message.destination())
+                .map(|(_, action)| action.clone());
+        }
+        None
+    }
+}
@@ -0,0 +1,175 @@
+use fyrox::{
+    core::{uuid::{Uuid, uuid}, visitor::prelude::*, reflect::prelude::*, TypeUuidProvider},
+    event::Event, impl_component_provider,
+    script::{ScriptContext, ScriptDeinitContext, ScriptTrait},
+};
+use fyrox::core::algebra::Vector3;
+use fyrox::core::pool::Handle;
+use fyrox::scene::node::Node;
+use fyrox::scene::rigidbody::{RigidBody, RigidBodyType};
+use fyrox::script::{ScriptMessageContext, ScriptMessagePayload};
+use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
+
+#[derive(Debug, Visit, Reflect, Clone, PartialEq, AsRefStr, EnumString, EnumVariantNames)]
+pub enum PlatformMode {
+    /// Travels to the last waypoint and back again.
+    PingPong,
+    /// Returns from the last waypoint straight to the first one.
+    Loop,
+    /// Travels one way along the path every time it is triggered.
+    Triggered,
+}
+
+impl Default for PlatformMode {
+    fn default() -> Self {
+        PlatformMode::PingPong
+    }
+}
+
+/// Starts a triggered platform, sent by the player when landing on it or by other scripts.
+#[derive(Debug, Clone)]
+pub struct PlatformTriggerMessage {
+    /// Sent by the player landing on the platform, which only counts with `trigger_on_contact`.
+    pub contact: bool,
+}
+
+#[derive(Visit, Reflect, Default, Debug, Clone)]
+pub struct MovingPlatform {
+    pub mode: PlatformMode,
+    pub speed: f32,
+    /// Seconds to wait at the first and last waypoint.
+    pub wait_time: f32,
+    /// Triggered platforms start when the player lands on them.
+    pub trigger_on_contact: bool,
+    /// Parent of the waypoint nodes, visited in child order.
+    path: Handle<Node>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    waypoints: Vec<Vector3<f32>>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    target: usize,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    backwards: bool,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    wait_timer: f32,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    moving: bool,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    velocity: Vector3<f32>,
+}
+
+impl MovingPlatform {
+    pub fn velocity(&self) -> Vector3<f32> {
+        self.velocity
+    }
+
+    pub fn trigger(&mut self) {
+        if self.mode == PlatformMode::Triggered && !self.moving {
+            self.moving = true;
+            self.wait_timer = 0.0;
+        }
+    }
+
+    fn is_end(&self, index: usize) -> bool {
+        index == 0 || index + 1 == self.waypoints.len()
+    }
+
+    fn advance(&mut self) {
+        let last = self.waypoints.len() - 1;
+        match self.mode {
+            PlatformMode::Loop => self.target = (self.target + 1) % self.waypoints.len(),
+            PlatformMode::PingPong | PlatformMode::Triggered => {
+                if self.target == last {
+                    self.backwards = true;
+                } else if self.target == 0 {
+                    self.backwards = false;
+                }
+                self.target = if self.backwards { self.target - 1 } else { self.target + 1 };
+            }
+        }
+    }
+
+    /// Works out the velocity that moves the platform towards its next waypoint this frame.
+    fn step(&mut self, position: Vector3<f32>, dt: f32) -> Vector3<f32> {
+        if self.waypoints.len() < 2 || !self.moving || dt <= 0.0 {
+            return Vector3::default();
+        }
+        if self.wait_timer > 0.0 {
+            self.wait_timer -= dt;
+            return Vector3::default();
+        }
+
+        let offset = self.waypoints[self.target] - position;
+        let distance = offset.norm();
+        if distance <= self.speed * dt {
+            let reached = self.target;
+            self.advance();
+            if self.is_end(reached) {
+                self.wait_timer = self.wait_time;
+                if self.mode == PlatformMode::Triggered {
+                    self.moving = false;
+                }
+            }
+            return offset / dt;
+        }
+        offset.scale(self.speed / distance)
+    }
+}
+
+impl_component_provider!(MovingPlatform);
+
+impl TypeUuidProvider for MovingPlatform {
+    fn type_uuid() -> Uuid {
+        uuid!("c47e2b19-8f3a-4d6c-9e15-3a7b0d2f6c84")
+    }
+}
+
+impl ScriptTrait for MovingPlatform {
+    fn on_init(&mut self, _context: &mut ScriptContext) {}
+
+    fn on_start(&mut self, context: &mut ScriptContext) {
+        context.message_dispatcher.subscribe_to::<PlatformTriggerMessage>(context.handle);
+        let graph = &mut context.scene.graph;
+        // Waypoints are captured once so they don't follow the platform if they are parented to it.
+        self.waypoints = graph
+            .try_get(self.path)
+            .map(|path| path.children().iter().map(|c| graph[*c].global_position()).collect())
+            .unwrap_or_default();
+        // A triggered platform waits at the first waypoint, so its first trip is to the second one.
+        self.target = if self.mode == PlatformMode::Triggered && self.waypoints.len() > 1 { 1 } else { 0 };
+        self.moving = self.mode != PlatformMode::Triggered;
+        if let Some(rigid_body) = graph[context.handle].cast_mut::<RigidBody>() {
+            rigid_body.set_body_type(RigidBodyType::KinematicVelocityBased);
+        }
+    }
+
+    fn on_deinit(&mut self, _context: &mut ScriptDeinitContext) {}
+
+    fn on_os_event(&mut self, _event: &Event<()>, _context: &mut ScriptContext) {}
+
+    fn on_update(&mut self, context: &mut ScriptContext) {
+        let position = context.scene.graph[context.handle].global_position();
+        self.velocity = self.step(position, context.dt);
+        if let Some(rigid_body) = context.scene.graph[context.handle].cast_mut::<RigidBody>() {
+            rigid_body.set_lin_vel(self.velocity);
+        }
+    }
+
+    fn on_message(&mut self, message: &mut dyn ScriptMessagePayload, _ctx: &mut ScriptMessageContext) {
+        if let Some(PlatformTriggerMessage { contact }) = message.downcast_ref::<PlatformTriggerMessage>() {
+            if !contact || self.trigger_on_contact {
+                self.trigger();
+            }
+        }
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+}
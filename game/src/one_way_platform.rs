@@ -0,0 +1,102 @@
+use fyrox::{
+    core::{uuid::{Uuid, uuid}, visitor::prelude::*, reflect::prelude::*, TypeUuidProvider},
+    event::Event, impl_component_provider,
+    script::{ScriptContext, ScriptDeinitContext, ScriptTrait},
+};
+use fyrox::core::algebra::Vector3;
+use fyrox::core::pool::Handle;
+use fyrox::scene::collider::{BitMask, Collider};
+use fyrox::scene::graph::Graph;
+use fyrox::scene::node::Node;
+use fyrox::scene::rigidbody::RigidBody;
+use crate::player_controller::{PlayerController, PLAYER_GROUP};
+
+/// True when a contact normal, pointing from the platform towards the player, is a landing on the
+/// top surface rather than a hit from the side or below.
+pub fn is_top_contact(normal_towards_player: &Vector3<f32>, threshold: f32) -> bool {
+    normal_towards_player.y > threshold
+}
+
+/// A one-way platform only collides with a player that is on top of it and not moving up relative
+/// to it, so the player can jump through from below and land from above.
+pub fn blocks_player(player_above: bool, relative_velocity: &Vector3<f32>, dropping_through: bool) -> bool {
+    player_above && relative_velocity.y <= f32::EPSILON && !dropping_through
+}
+
+/// Collider the player can jump up through and land on. While the player should pass it stops
+/// colliding with the player's group only, so debris, traffic and packages still rest on it.
+#[derive(Visit, Reflect, Default, Debug, Clone)]
+pub struct OneWayPlatform {
+    /// Height of the walkable surface above the node's origin.
+    pub surface_offset: f32,
+    /// How far below the surface the player's origin may be and still count as on top.
+    pub tolerance: f32,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    player: Handle<Node>,
+}
+
+impl OneWayPlatform {
+    fn velocity_of(graph: &Graph, handle: Handle<Node>) -> Vector3<f32> {
+        graph
+            .find_up(handle, &mut |n| n.cast::<RigidBody>().is_some())
+            .and_then(|(_, n)| n.cast::<RigidBody>())
+            .map(|b| b.lin_vel())
+            .unwrap_or_default()
+    }
+}
+
+impl_component_provider!(OneWayPlatform);
+
+impl TypeUuidProvider for OneWayPlatform {
+    fn type_uuid() -> Uuid {
+        uuid!("0f6d2a83-5c1e-4b97-a2d8-6e4c9b1f7a35")
+    }
+}
+
+impl ScriptTrait for OneWayPlatform {
+    fn on_init(&mut self, _context: &mut ScriptContext) {}
+
+    fn on_start(&mut self, context: &mut ScriptContext) {
+        if let Some((player, _)) = context.scene.graph.find_from_root(&mut |n| n.has_script::<PlayerController>()) {
+            self.player = player;
+        }
+    }
+
+    fn on_deinit(&mut self, _context: &mut ScriptDeinitContext) {}
+
+    fn on_os_event(&mut self, _event: &Event<()>, _context: &mut ScriptContext) {}
+
+    fn on_update(&mut self, context: &mut ScriptContext) {
+        let graph = &mut context.scene.graph;
+        let player = match graph.try_get(self.player) {
+            Some(player) => player,
+            None => return,
+        };
+        let dropping_through = player
+            .try_get_script::<PlayerController>()
+            .map_or(false, |p| p.is_dropping_through());
+        let top = graph[context.handle].global_position().y + self.surface_offset;
+        let player_above = player.global_position().y >= top - self.tolerance;
+        let relative_velocity = Self::velocity_of(graph, self.player) - Self::velocity_of(graph, context.handle);
+
+        let solid = blocks_player(player_above, &relative_velocity, dropping_through);
+        if let Some(collider) = graph[context.handle].cast_mut::<Collider>() {
+            let mut groups = collider.collision_groups();
+            let filter = if solid {
+                BitMask(groups.filter.0 | PLAYER_GROUP.0)
+            } else {
+                BitMask(groups.filter.0 & !PLAYER_GROUP.0)
+            };
+            if groups.filter != filter {
+                groups.filter = filter;
+                collider.set_collision_groups(groups);
+            }
+        }
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+}
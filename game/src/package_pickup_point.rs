@@ -1,4 +1,3 @@
-
 use fyrox::{
     core::{uuid::{Uuid, uuid}, visitor::prelude::*, reflect::prelude::*, TypeUuidProvider},
     event::Event, impl_component_provider,
@@ -6,38 +5,68 @@ use fyrox::{
 };
 use fyrox::core::pool::Handle;
 use fyrox::material::SharedMaterial;
-use fyrox::scene::collider::Collider;
 use fyrox::scene::graph::Graph;
 use fyrox::scene::mesh::Mesh;
 use fyrox::scene::node::Node;
-use crate::player_controller::PlayerController;
+use fyrox::script::{ScriptMessageContext, ScriptMessagePayload};
+
+/// Sent by the player to a drop-off point that accepted at least one package.
+#[derive(Debug, Clone)]
+pub struct PackageDeliveredMessage {
+    pub count: usize,
+}
 
-#[derive(Visit, Reflect, Default, Debug, Clone)]
+#[derive(Visit, Reflect, Debug, Clone)]
 pub struct PackagePickupPoint {
     pub is_drop_off: bool,
 
+    /// Where packages from this pickup are headed, or which packages this drop-off accepts.
+    /// Leave empty to match any destination.
+    #[visit(optional)]
+    pub destination: String,
+
+    /// How many packages a pickup hands out per visit, limited by the player's capacity.
+    #[visit(optional)]
+    pub package_count: u32,
+
+    /// Seconds to deliver packages from this pickup, zero means no deadline.
+    #[visit(optional)]
+    pub delivery_deadline: f32,
+
     point_mesh: Handle<Node>,
 
     deactivated_material: SharedMaterial,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    delivered: bool,
+}
+
+impl Default for PackagePickupPoint {
+    fn default() -> Self {
+        Self {
+            is_drop_off: false,
+            destination: String::new(),
+            package_count: 1,
+            delivery_deadline: 0.0,
+            point_mesh: Handle::NONE,
+            deactivated_material: Default::default(),
+            delivered: false,
+        }
+    }
 }
 
 impl PackagePickupPoint {
-    pub fn has_player_contact(&self, handle: Handle<Node>, graph: &Graph) -> bool {
-        if let Some(collider) = graph.try_get_of_type::<Collider>(handle) {
-            for contact in collider.intersects(&graph.physics) {
-                if contact.has_any_active_contact {
-                    let opposing_handle = if contact.collider1.eq(&handle) { contact.collider2 } else { contact.collider1 };
-                    if let Some(parent) = graph.try_get(opposing_handle).map(|n| n.parent()) {
-                        if graph.find_up(parent, &mut |n| {
-                            return n.script().and_then(|s|s.cast::<PlayerController>()).is_some();
-                        }).is_some() {
-                            return true;
-                        }
-                    }
-                }
-            }
+    /// True once packages were dropped off here.
+    pub fn is_delivered(&self) -> bool {
+        self.delivered
+    }
+
+    pub fn deactivate(&self, handle: Handle<Node>, graph: &mut Graph) {
+        graph[handle].set_enabled(false);
+        if let Some(mesh) = graph[self.point_mesh].cast_mut::<Mesh>() {
+            mesh.surfaces_mut()[0].set_material(self.deactivated_material.clone())
         }
-        false
     }
 }
 
@@ -52,21 +81,20 @@ impl TypeUuidProvider for PackagePickupPoint {
 impl ScriptTrait for PackagePickupPoint {
     fn on_init(&mut self, _context: &mut ScriptContext) {}
 
-    fn on_start(&mut self, _context: &mut ScriptContext) {}
+    fn on_start(&mut self, context: &mut ScriptContext) {
+        context.message_dispatcher.subscribe_to::<PackageDeliveredMessage>(context.handle);
+    }
 
     fn on_deinit(&mut self, _context: &mut ScriptDeinitContext) {}
 
     fn on_os_event(&mut self, _event: &Event<()>, _context: &mut ScriptContext) {}
 
-    fn on_update(&mut self, context: &mut ScriptContext) {
-        if self.is_drop_off {
-            let graph = &mut context.scene.graph;
-            if self.has_player_contact(context.handle, &graph) {
-                graph[context.handle].set_enabled(false);
-                if let Some(mesh) = graph[self.point_mesh].cast_mut::<Mesh>() {
-                    mesh.surfaces_mut()[0].set_material(self.deactivated_material.clone())
-                }
-            }
+    fn on_update(&mut self, _context: &mut ScriptContext) {}
+
+    fn on_message(&mut self, message: &mut dyn ScriptMessagePayload, ctx: &mut ScriptMessageContext) {
+        if message.downcast_ref::<PackageDeliveredMessage>().is_some() && self.is_drop_off {
+            self.delivered = true;
+            self.deactivate(ctx.handle, &mut ctx.scene.graph);
         }
     }
 
@@ -74,4 +102,4 @@ impl ScriptTrait for PackagePickupPoint {
         Self::type_uuid()
     }
 }
-    
\ No newline at end of file
+
@@ -0,0 +1,127 @@
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct Package {
+    pub health: u8,
+    pub destination: String,
+    /// Seconds left to deliver the package before it is lost.
+    pub deadline: Option<f32>,
+}
+
+impl Package {
+    pub fn new(health: u8, destination: &str) -> Self {
+        Self {
+            health,
+            destination: destination.to_string(),
+            deadline: None,
+        }
+    }
+
+    pub fn with_deadline(mut self, seconds: f32) -> Self {
+        self.deadline = Some(seconds);
+        self
+    }
+
+    /// An empty destination on either side acts as a wildcard.
+    pub fn is_for(&self, destination: &str) -> bool {
+        self.destination.is_empty() || destination.is_empty() || self.destination == destination
+    }
+}
+
+/// The packages the courier is carrying, bottom of the stack first.
+#[derive(Default, Debug, Clone)]
+pub struct PackageStack {
+    pub capacity: usize,
+    packages: Vec<Package>,
+}
+
+impl PackageStack {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            packages: Vec::with_capacity(capacity),
+        }
+    }
+
+    pub fn packages(&self) -> &[Package] {
+        &self.packages
+    }
+
+    pub fn len(&self) -> usize {
+        self.packages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.packages.is_empty()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.packages.len() >= self.capacity
+    }
+
+    pub fn push(&mut self, package: Package) -> bool {
+        if self.is_full() {
+            return false;
+        }
+        self.packages.push(package);
+        true
+    }
+
+    /// Removes the package on top of the stack.
+    pub fn take_top(&mut self) -> Option<Package> {
+        self.packages.pop()
+    }
+
+    pub fn clear(&mut self) {
+        self.packages.clear();
+    }
+
+    /// Splits the damage evenly across the stack, the top packages take the remainder.
+    pub fn apply_damage(&mut self, damage: u8) {
+        let count = self.packages.len();
+        if count == 0 {
+            return;
+        }
+        let share = damage as usize / count;
+        let remainder = damage as usize % count;
+        for (i, package) in self.packages.iter_mut().rev().enumerate() {
+            let hit = share + if i < remainder { 1 } else { 0 };
+            package.health = package.health.saturating_sub(hit as u8);
+        }
+    }
+
+    pub fn repair(&mut self, amount: u8, max_health: u8) {
+        for package in self.packages.iter_mut() {
+            package.health = package.health.saturating_add(amount).min(max_health);
+        }
+    }
+
+    /// Removes packages that have no health left and returns how many were lost.
+    pub fn remove_destroyed(&mut self) -> usize {
+        let before = self.packages.len();
+        self.packages.retain(|p| p.health > 0);
+        before - self.packages.len()
+    }
+
+    /// Counts down the delivery deadlines and removes the expired packages, returns how many were lost.
+    pub fn tick_deadlines(&mut self, dt: f32) -> usize {
+        for deadline in self.packages.iter_mut().filter_map(|p| p.deadline.as_mut()) {
+            *deadline -= dt;
+        }
+        let before = self.packages.len();
+        self.packages.retain(|p| p.deadline.map_or(true, |d| d > 0.0));
+        before - self.packages.len()
+    }
+
+    /// Removes and returns every package bound for the given destination.
+    pub fn deliver(&mut self, destination: &str) -> Vec<Package> {
+        let mut delivered = Vec::new();
+        let mut i = 0;
+        while i < self.packages.len() {
+            if self.packages[i].is_for(destination) {
+                delivered.push(self.packages.remove(i));
+            } else {
+                i += 1;
+            }
+        }
+        delivered
+    }
+}
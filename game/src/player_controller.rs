@@ -6,23 +6,51 @@ use fyrox::{
 use fyrox::core::algebra::{UnitQuaternion, Vector3};
 use fyrox::core::log::Log;
 use fyrox::core::pool::Handle;
-use fyrox::event::{ElementState, VirtualKeyCode, WindowEvent};
-use fyrox::scene::collider::Collider;
+use fyrox::scene::collider::{BitMask, Collider};
 use fyrox::scene::graph::Graph;
 use fyrox::scene::node::Node;
 use fyrox::scene::rigidbody::RigidBody;
 use fyrox::script::{ScriptMessageContext, ScriptMessagePayload};
 use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
-use crate::package_pickup_point::PackagePickupPoint;
+use crate::airtime::{AirtimeRules, AirtimeTracker, Landing, LaunchKind};
+use crate::charging_station::ChargingStation;
+use crate::checkpoint::{Checkpoint, CheckpointState};
+use crate::destructible::LevelResetMessage;
+use crate::difficulty::DifficultyModifiers;
+use crate::events::{publish, GameplayEvent};
+use crate::input::{InputAction, InputState};
+use crate::jump_pad::LaunchMessage;
+use crate::moving_platform::{MovingPlatform, PlatformTriggerMessage};
+use crate::one_way_platform::{is_top_contact, OneWayPlatform};
+use crate::contacts::{gather, Contact, ContactKind, ContactService};
+use crate::collectible::{CollectibleEffect, CollectibleMessage, PowerUps};
+use crate::package_pickup_point::{PackageDeliveredMessage, PackagePickupPoint};
+use crate::package_stack::{Package, PackageStack};
+use crate::pursuer::PursuerCaughtMessage;
 use crate::player_controller::MoveDirection::{Left, Right};
-use crate::reverse_direction::ReverseDirection;
-use crate::terrain_effect::{DamageMessage, TerrainEffect};
+use crate::reverse_direction::ReverseMessage;
+use crate::tuning::GameplayTuning;
+use crate::terrain_effect::{DamageMessage, TerrainContact, TerrainEffect, TerrainStack};
 
-#[derive(Visit, Reflect, Default, Debug, Clone)]
+/// Collision group the player's collider is the only member of, so colliders can leave out just
+/// the player, e.g. a one-way platform the player is jumping through.
+pub const PLAYER_GROUP: BitMask = BitMask(1 << 31);
+
+#[derive(Visit, Reflect, Debug, Clone)]
 pub struct PlayerController {
     accel_force: f32,
     max_speed: f32,
     jump_force: f32,
+    /// Downward speed while falling, as a multiple of `jump_force`.
+    #[visit(optional)]
+    fall_multiplier: f32,
+    /// Minimum vertical component of a contact normal that counts as ground.
+    #[visit(optional)]
+    ground_normal_threshold: f32,
+    /// Tunable values as the prefab set them, restored for keys missing from the tuning file.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    prefab_tuning: Option<(f32, f32, f32, f32, f32)>,
     direction: MoveDirection,
 
     pub player_health: u8,
@@ -31,22 +59,139 @@ pub struct PlayerController {
     pub actual_player_health: u8,
 
     pub package_health: u8,
+
+    #[visit(optional)]
+    pub carry_capacity: u32,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    pub cargo: PackageStack,
+
+    #[visit(optional)]
+    pub battery_capacity: f32,
+    /// Charge used per second for each unit of acceleration force applied.
+    #[visit(optional)]
+    battery_drain: f32,
+    /// Fraction of the acceleration force left when the battery is empty.
+    #[visit(optional)]
+    empty_battery_accel: f32,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    pub actual_battery_charge: f32,
+
     #[visit(skip)]
     #[reflect(hidden)]
-    pub actual_package_health: u8,
+    pub power_ups: PowerUps,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    pub terrain: TerrainStack,
+    /// Shown while a terrain effect lingers after leaving its zone, e.g. oil on the tyres.
+    #[visit(optional)]
+    lingering_visual: Handle<Node>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    pub difficulty: DifficultyModifiers,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    platform: Handle<Node>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    carried_velocity: Vector3<f32>,
 
     collider: Handle<Node>,
 
     package: Handle<Node>,
+    /// Extra bundles stacked on top of `package`, shown as the stack grows.
+    #[visit(optional)]
+    package_stack: Vec<Handle<Node>>,
     jump_sound: Handle<Node>,
 
     player_model: Handle<Node>,
     #[visit(skip)]
     #[reflect(hidden)]
     jump_held: bool,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    grounded: bool,
+    /// Seconds one-way platforms stay passable after pressing down.
+    #[visit(optional)]
+    drop_through_time: f32,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    drop_through_timer: f32,
+
+    /// Upward speed on take-off that counts as launching off a ramp.
+    #[visit(optional)]
+    ramp_launch_speed: f32,
+    /// Seconds in the air that count as big air.
+    #[visit(optional)]
+    big_air_time: f32,
+    /// Largest tilt, in degrees, that still counts as landing upright.
+    #[visit(optional)]
+    upright_angle: f32,
+    /// Damage spread over the packages when landing a crash.
+    #[visit(optional)]
+    crash_package_damage: u8,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    pub airtime: AirtimeTracker,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    touching_point: Handle<Node>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    last_checkpoint: Handle<Node>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    checkpoint: Option<CheckpointState>,
 }
 
-#[derive(Debug, Visit, Reflect, Clone, AsRefStr, EnumString, EnumVariantNames)]
+impl Default for PlayerController {
+    fn default() -> Self {
+        Self {
+            accel_force: 0.0,
+            max_speed: 0.0,
+            jump_force: 0.0,
+            fall_multiplier: 2.0,
+            ground_normal_threshold: 0.7,
+            direction: Default::default(),
+            player_health: 0,
+            actual_player_health: 0,
+            package_health: 0,
+            carry_capacity: 1,
+            cargo: Default::default(),
+            battery_capacity: 100.0,
+            battery_drain: 0.01,
+            empty_battery_accel: 0.1,
+            actual_battery_charge: 0.0,
+            power_ups: Default::default(),
+            terrain: Default::default(),
+            lingering_visual: Handle::NONE,
+            difficulty: Default::default(),
+            platform: Handle::NONE,
+            carried_velocity: Vector3::default(),
+            collider: Handle::NONE,
+            package: Handle::NONE,
+            package_stack: Vec::new(),
+            jump_sound: Handle::NONE,
+            player_model: Handle::NONE,
+            jump_held: false,
+            grounded: false,
+            drop_through_time: 0.3,
+            drop_through_timer: 0.0,
+            ramp_launch_speed: 2.0,
+            big_air_time: 1.0,
+            upright_angle: 45.0,
+            crash_package_damage: 0,
+            airtime: Default::default(),
+            touching_point: Handle::NONE,
+            last_checkpoint: Handle::NONE,
+            checkpoint: None,
+            prefab_tuning: None,
+        }
+    }
+}
+
+#[derive(Debug, Visit, Reflect, Clone, PartialEq, AsRefStr, EnumString, EnumVariantNames)]
 pub enum MoveDirection {
     Left,
     Right,
@@ -58,6 +203,23 @@ impl Default for MoveDirection {
     }
 }
 
+impl MoveDirection {
+    pub fn opposite(&self) -> Self {
+        match self {
+            Left => Right,
+            Right => Left,
+        }
+    }
+
+    /// Sign of the world x axis the player moves along.
+    pub fn sign(&self) -> f32 {
+        match self {
+            Left => 1.0,
+            Right => -1.0,
+        }
+    }
+}
+
 impl_component_provider!(PlayerController);
 
 impl TypeUuidProvider for PlayerController {
@@ -68,28 +230,36 @@ impl TypeUuidProvider for PlayerController {
 
 pub struct ContactFlags {
     ground_contact: bool,
-    terrain_effects: (f32, f32),
+    terrain_contacts: Vec<TerrainContact>,
     reverse_direction: bool,
-    package_pickup: bool,
-    package_drop: bool,
-    
+    package_point: Handle<Node>,
+    charge_rate: f32,
+    checkpoint: Handle<Node>,
+    platform: Handle<Node>,
+    platform_velocity: Vector3<f32>,
 }
 
 impl Default for ContactFlags {
     fn default() -> Self {
         Self {
             ground_contact: false,
-            terrain_effects: (0.0f32, 0.0f32),
+            terrain_contacts: Vec::new(),
             reverse_direction: false,
-            package_pickup: false,
-            package_drop: false,
+            package_point: Handle::NONE,
+            charge_rate: 0.0,
+            checkpoint: Handle::NONE,
+            platform: Handle::NONE,
+            platform_velocity: Vector3::default(),
         }
     }
 }
 
 impl PlayerController {
     pub fn rotate_player(&self, graph: &mut Graph, mesh_ref: Handle<Node>) {
-        let player_mesh = &mut graph[mesh_ref];
+        let player_mesh = match graph.try_get_mut(mesh_ref) {
+            Some(player_mesh) => player_mesh,
+            None => return,
+        };
         let angle = player_mesh.local_transform().rotation().angle();
         player_mesh.local_transform_mut().set_rotation(
             UnitQuaternion::from_axis_angle(&Vector3::y_axis(),
@@ -101,83 +271,242 @@ impl PlayerController {
         );
     }
 
-    pub fn process_collisions(&self, graph: &Graph) -> ContactFlags {
-        let mut flags = ContactFlags::default();
-        if let Some(collider) = graph
-            .try_get(self.collider)
-            .and_then(|n| n.cast::<Collider>())
-        {
-            for contact in collider.contacts(&graph.physics) {
-                let opposing_handle = if contact.collider1.eq(&self.collider) { contact.collider2 } else { contact.collider1 };
-                if let Some(opposing_collider) = graph.try_get_of_type::<Collider>(opposing_handle) {
-                    if opposing_collider.has_script::<ReverseDirection>() {
-                        flags.reverse_direction = true;
-                    }
+    pub fn apply_tuning(&mut self, tuning: &GameplayTuning) {
+        // Values left out of the file go back to what the prefab set, not the last tuned value.
+        let prefab = self.prefab_tuning.get_or_insert_with(|| (
+            self.accel_force,
+            self.max_speed,
+            self.jump_force,
+            self.fall_multiplier,
+            self.ground_normal_threshold,
+        ));
+        self.accel_force = tuning.accel_force.unwrap_or(prefab.0);
+        self.max_speed = tuning.max_speed.unwrap_or(prefab.1);
+        self.jump_force = tuning.jump_force.unwrap_or(prefab.2);
+        self.fall_multiplier = tuning.fall_multiplier.unwrap_or(prefab.3);
+        self.ground_normal_threshold = tuning.ground_normal_threshold.unwrap_or(prefab.4);
+    }
+
+    /// Takes effect right away, current health keeps its share of the new maximum.
+    pub fn apply_difficulty(&mut self, difficulty: DifficultyModifiers) {
+        let old_max = self.difficulty.scale_player_health(self.player_health);
+        let new_max = difficulty.scale_player_health(self.player_health);
+        if old_max > 0 && self.actual_player_health > 0 {
+            let health = (self.actual_player_health as f32 * new_max as f32 / old_max as f32).round();
+            self.actual_player_health = (health as u8).max(1);
+        }
+        self.difficulty = difficulty;
+    }
+
+    /// Returns true when the action made the player jump.
+    pub fn handle_action(&mut self, action: InputAction, handle: Handle<Node>, graph: &mut Graph) -> bool {
+        match action {
+            InputAction::JumpPressed => {
+                if !self.grounded {
+                    return false;
                 }
-                for manifold in contact.manifolds.iter() {
-                    if manifold.local_n1.y.abs() > 0.7 || manifold.local_n2.y.abs() > 0.7 {
-                        flags.ground_contact = true;
+                if let Some(rigid_body) = graph[handle].cast_mut::<RigidBody>() {
+                    let vel = rigid_body.lin_vel();
+                    rigid_body.set_lin_vel(Vector3::new(vel.x, self.jump_force, 0.0));
+                    if let Some(sound) = graph.try_get_mut(self.jump_sound) {
+                        sound.as_sound_mut().stop();
+                        sound.as_sound_mut().play();
                     }
+                    self.jump_held = true;
+                    self.airtime.launch(LaunchKind::Jump);
+                    return true;
                 }
             }
+            InputAction::JumpReleased => self.jump_held = false,
+            InputAction::DropThrough => self.drop_through_timer = self.drop_through_time,
+        }
+        false
+    }
 
-            for contact in collider.intersects(&graph.physics) {
-                if contact.has_any_active_contact {
-                    let opposing_handle = if contact.collider1.eq(&self.collider) { contact.collider2 } else { contact.collider1 };
-                    if let Some(opposing_collider) = graph.try_get_of_type::<Collider>(opposing_handle) {
-                        if let Some(terrain_effect) = opposing_collider.try_get_script::<TerrainEffect>() {
-                            flags.terrain_effects.0 += terrain_effect.accel_modifier;
-                            flags.terrain_effects.1 += terrain_effect.max_speed_mod;
-                        }
-                        if let Some(pickup_settings) = opposing_collider.try_get_script::<PackagePickupPoint>() {
-                            flags.package_pickup = !pickup_settings.is_drop_off;
-                            flags.package_drop = pickup_settings.is_drop_off;
-                            flags.reverse_direction = flags.package_drop;
-                        }
-                    }
+    pub fn model(&self) -> Handle<Node> {
+        self.player_model
+    }
+
+    pub fn direction(&self) -> &MoveDirection {
+        &self.direction
+    }
+
+    pub fn set_direction(&mut self, direction: MoveDirection, handle: Handle<Node>, graph: &mut Graph) {
+        if self.direction == direction {
+            return;
+        }
+        self.direction = direction;
+        self.rotate_player(graph, self.player_model);
+        if let Some(rigid_body) = graph[handle].cast_mut::<RigidBody>() {
+            let vel = rigid_body.lin_vel();
+            rigid_body.set_lin_vel(Vector3::new(0.0, vel.y, vel.z));
+        }
+    }
+
+    pub fn update_package_visuals(&self, graph: &mut Graph) {
+        let slots = std::iter::once(&self.package).chain(self.package_stack.iter());
+        for (i, slot) in slots.enumerate() {
+            if let Some(node) = graph.try_get_mut(*slot) {
+                node.set_visibility(i < self.cargo.len());
+            }
+        }
+    }
+
+    /// Loads or unloads packages at a pickup point, returns true when the player should turn around.
+    pub fn visit_package_point(&mut self, point: Handle<Node>, context: &mut ScriptContext) -> bool {
+        let settings = match context.scene.graph[point].try_get_script::<PackagePickupPoint>() {
+            Some(settings) => settings,
+            None => return false,
+        };
+        if settings.is_drop_off {
+            let delivered = self.cargo.deliver(&settings.destination);
+            if !delivered.is_empty() {
+                context.message_sender.send_to_target(point, PackageDeliveredMessage {
+                    count: delivered.len(),
+                });
+                publish(context.plugins, GameplayEvent::Delivery {
+                    player: context.handle,
+                    point,
+                    count: delivered.len(),
+                    health: delivered.iter().map(|p| p.health as u32).sum(),
+                    max_health: delivered.len() as u32 * self.difficulty.scale_package_health(self.package_health) as u32,
+                });
+            }
+            self.cargo.is_empty()
+        } else {
+            let before = self.cargo.len();
+            for _ in 0..settings.package_count {
+                let mut package = Package::new(
+                    self.difficulty.scale_package_health(self.package_health),
+                    &settings.destination,
+                );
+                if settings.delivery_deadline > 0.0 {
+                    package = package.with_deadline(self.difficulty.scale_deadline(settings.delivery_deadline));
+                }
+                if !self.cargo.push(package) {
+                    break;
                 }
             }
+            if self.cargo.len() > before {
+                publish(context.plugins, GameplayEvent::Pickup {
+                    player: context.handle,
+                    point,
+                    count: self.cargo.len() - before,
+                });
+            }
+            false
         }
+    }
 
-        flags
+    pub fn save_checkpoint(&mut self, position: Vector3<f32>) {
+        self.checkpoint = Some(CheckpointState {
+            position,
+            direction: self.direction.clone(),
+            player_health: self.actual_player_health,
+            cargo: self.cargo.clone(),
+            battery_charge: self.actual_battery_charge,
+        });
+    }
+
+    pub fn restore_checkpoint(&mut self, handle: Handle<Node>, graph: &mut Graph) {
+        if let Some(state) = self.checkpoint.clone() {
+            if self.direction != state.direction {
+                self.rotate_player(graph, self.player_model);
+            }
+            self.direction = state.direction;
+            self.actual_player_health = state.player_health;
+            self.cargo = state.cargo;
+            self.actual_battery_charge = state.battery_charge;
+            if let Some(rigid_body) = graph[handle].cast_mut::<RigidBody>() {
+                rigid_body.local_transform_mut().set_position(state.position);
+                rigid_body.set_lin_vel(Vector3::default());
+            }
+        }
+    }
+
+    fn airtime_rules(&self) -> AirtimeRules {
+        AirtimeRules {
+            ramp_launch_speed: self.ramp_launch_speed,
+            big_air_time: self.big_air_time,
+            upright_cos: self.upright_angle.to_radians().cos(),
+        }
+    }
+
+    pub fn is_dropping_through(&self) -> bool {
+        self.drop_through_timer > 0.0
+    }
+
+    /// Side and bottom hits on one-way platforms don't count as ground, the platform lets those through.
+    fn is_ground_manifold(&self, n1: &Vector3<f32>, n2: &Vector3<f32>, normal_towards_player: &Vector3<f32>, opposing: Handle<Node>, graph: &Graph) -> bool {
+        if n1.y.abs() <= self.ground_normal_threshold && n2.y.abs() <= self.ground_normal_threshold {
+            return false;
+        }
+        if graph.try_get(opposing).map_or(false, |n| n.has_script::<OneWayPlatform>()) {
+            return is_top_contact(normal_towards_player, self.ground_normal_threshold);
+        }
+        true
+    }
+
+    pub fn collider(&self) -> Handle<Node> {
+        self.collider
+    }
+
+    pub fn set_collider(&mut self, collider: Handle<Node>) {
+        self.collider = collider;
     }
 
-    pub fn has_ground_contact(&self, graph: &Graph) -> bool {
+    /// Ground and platform contacts come from the player's manifolds, sensors from the contact
+    /// service when the game runs.
+    pub fn process_collisions(&self, handle: Handle<Node>, graph: &Graph, contacts: Option<&ContactService>) -> ContactFlags {
+        let mut flags = ContactFlags::default();
         if let Some(collider) = graph
             .try_get(self.collider)
             .and_then(|n| n.cast::<Collider>())
         {
             for contact in collider.contacts(&graph.physics) {
+                let opposing_handle = if contact.collider1.eq(&self.collider) { contact.collider2 } else { contact.collider1 };
                 for manifold in contact.manifolds.iter() {
-                    if manifold.local_n1.y.abs() > 0.7 || manifold.local_n2.y.abs() > 0.7 {
-                        return true;
+                    let normal = if contact.collider1.eq(&self.collider) { -manifold.normal } else { manifold.normal };
+                    if self.is_ground_manifold(&manifold.local_n1, &manifold.local_n2, &normal, opposing_handle, graph) {
+                        flags.ground_contact = true;
+                        if let Some((platform, node)) = graph.find_up(opposing_handle, &mut |n| n.has_script::<MovingPlatform>()) {
+                            flags.platform = platform;
+                            flags.platform_velocity = node.try_get_script::<MovingPlatform>().unwrap().velocity();
+                        }
                     }
                 }
             }
         }
-        false
-    }
 
-    pub fn has_terrain_effect(&self, graph: &Graph) -> (f32, f32) {
-        let mut res = (0.0f32, 0.0f32);
-        if let Some(collider) = graph
-            .try_get_of_type::<Collider>(self.collider)
-        {
-            for contact in collider.intersects(&graph.physics) {
-                if contact.has_any_active_contact {
-                    let opposing_handle = if contact.collider1.eq(&self.collider) { contact.collider2 } else { contact.collider1 };
-                    if let Some(opposing_collider) = graph.try_get_of_type::<Collider>(opposing_handle) {
-                        if opposing_collider.has_script::<TerrainEffect>() {
-                            if let Some(terrain_effect) = opposing_collider.try_get_script::<TerrainEffect>() {
-                                res.0 += terrain_effect.accel_modifier;
-                                res.1 += terrain_effect.max_speed_mod;
-                            }
-                        }
+        let sensed: Vec<Contact> = match contacts {
+            Some(service) => service.contacts_of(handle).filter(|c| c.sensor).copied().collect(),
+            None => {
+                let mut sensed = Vec::new();
+                gather(handle, self.collider, graph, &mut sensed);
+                sensed.retain(|c| c.sensor);
+                sensed
+            }
+        };
+        for contact in sensed {
+            let opposing = &graph[contact.collider];
+            match contact.kind {
+                ContactKind::Terrain => {
+                    if let Some(terrain_effect) = opposing.try_get_script::<TerrainEffect>().filter(|t| t.is_active()) {
+                        flags.terrain_contacts.push(terrain_effect.contact(contact.collider));
                     }
                 }
+                ContactKind::PackagePoint => flags.package_point = contact.collider,
+                ContactKind::ChargingStation => {
+                    if let Some(station) = opposing.try_get_script::<ChargingStation>() {
+                        flags.charge_rate += station.charge_rate;
+                    }
+                }
+                ContactKind::Checkpoint => flags.checkpoint = contact.collider,
+                _ => (),
             }
         }
-        res
+
+        flags
     }
 }
 
@@ -186,94 +515,219 @@ impl ScriptTrait for PlayerController {
 
     fn on_start(&mut self, context: &mut ScriptContext) {
         context.message_dispatcher.subscribe_to::<DamageMessage>(context.handle);
-        self.actual_player_health = self.player_health;
-        self.actual_package_health = self.package_health;
+        context.message_dispatcher.subscribe_to::<CollectibleMessage>(context.handle);
+        context.message_dispatcher.subscribe_to::<ReverseMessage>(context.handle);
+        context.message_dispatcher.subscribe_to::<LaunchMessage>(context.handle);
+        context.message_dispatcher.subscribe_to::<PursuerCaughtMessage>(context.handle);
+        self.actual_player_health = self.difficulty.scale_player_health(self.player_health);
+        self.cargo = PackageStack::new(self.carry_capacity.max(1) as usize);
+        self.actual_battery_charge = self.battery_capacity * self.difficulty.starting_battery.clamp(0.0, 1.0);
+        self.update_package_visuals(&mut context.scene.graph);
+        if let Some(collider) = context.scene.graph.try_get_mut(self.collider).and_then(|n| n.cast_mut::<Collider>()) {
+            let mut groups = collider.collision_groups();
+            groups.memberships = PLAYER_GROUP;
+            collider.set_collision_groups(groups);
+        }
+        let start = context.scene.graph[context.handle].global_position();
+        self.save_checkpoint(start);
     }
 
     fn on_deinit(&mut self, _context: &mut ScriptDeinitContext) {}
 
-    fn on_os_event(&mut self, event: &Event<()>, context: &mut ScriptContext) {
-        if let Event::WindowEvent { event, .. } = event {
-            if let WindowEvent::KeyboardInput { input, .. } = event {
-                if let Some(keycode) = input.virtual_keycode {
-                    let is_pressed = input.state == ElementState::Pressed;
-                    let is_released = input.state == ElementState::Released;
-                    match keycode {
-                        VirtualKeyCode::Space => {
-                            if is_pressed && self.has_ground_contact(&context.scene.graph) {
-                                if let Some(rigid_body) = context.scene.graph[context.handle].cast_mut::<RigidBody>() {
-                                    let vel = rigid_body.lin_vel();
-                                    rigid_body.set_lin_vel(Vector3::new(vel.x, self.jump_force, 0.0));
-                                    context.scene.graph[self.jump_sound].as_sound_mut().stop();
-                                    context.scene.graph[self.jump_sound].as_sound_mut().play();
-                                    self.jump_held = true;
-                                }
-                            }
-                            if is_released && self.jump_held{
-                                self.jump_held = false;
-                            }
-                            
-                            
-                        }
-                        _ => (),
-                    }
-                }
-            }
-        }
-    }
+    fn on_os_event(&mut self, _event: &Event<()>, _context: &mut ScriptContext) {}
 
     fn on_update(&mut self, context: &mut ScriptContext) {
-        let flags = self.process_collisions(&context.scene.graph);
-        if flags.package_drop {
-            context.scene.graph[self.package].set_visibility(false);
-            self.actual_package_health = self.package_health;
+        let actions = InputState::find(context.plugins).map(|input| input.actions().to_vec()).unwrap_or_default();
+        for action in actions {
+            if self.handle_action(action, context.handle, &mut context.scene.graph) {
+                publish(context.plugins, GameplayEvent::Jump { player: context.handle });
+            }
         }
-        if flags.package_pickup {
-            context.scene.graph[self.package].set_visibility(true);
+        let mut flags = self.process_collisions(context.handle, &context.scene.graph, ContactService::find(context.plugins));
+        self.grounded = flags.ground_contact;
+        if flags.package_point != self.touching_point {
+            self.touching_point = flags.package_point;
+            if flags.package_point.is_some() && self.visit_package_point(flags.package_point, context) {
+                flags.reverse_direction = true;
+            }
         }
+        if flags.checkpoint.is_some() && flags.checkpoint != self.last_checkpoint {
+            self.last_checkpoint = flags.checkpoint;
+            if let Some(checkpoint) = context.scene.graph[flags.checkpoint]
+                .try_get_script::<Checkpoint>()
+                .filter(|c| c.is_active_for(&self.difficulty))
+            {
+                let position = checkpoint.spawn_position(flags.checkpoint, &context.scene.graph);
+                self.save_checkpoint(position);
+                publish(context.plugins, GameplayEvent::Checkpoint {
+                    player: context.handle,
+                    checkpoint: flags.checkpoint,
+                });
+            }
+        }
+        if self.player_health > 0 && self.actual_player_health == 0 {
+            publish(context.plugins, GameplayEvent::Death { player: context.handle });
+            self.restore_checkpoint(context.handle, &mut context.scene.graph);
+            context.message_sender.send_global(LevelResetMessage);
+        }
+        self.actual_battery_charge = (self.actual_battery_charge + flags.charge_rate * context.dt)
+            .min(self.battery_capacity);
+        self.power_ups.tick(context.dt);
+        self.drop_through_timer = (self.drop_through_timer - context.dt).max(0.0);
+        let (vertical_velocity, angular_velocity) = context.scene.graph[context.handle]
+            .cast::<RigidBody>()
+            .map(|b| (b.lin_vel().y, b.ang_vel().z))
+            .unwrap_or_default();
+        let up_dot = context.scene.graph[context.handle]
+            .up_vector()
+            .try_normalize(f32::EPSILON)
+            .map_or(1.0, |up| up.y);
+        let rules = self.airtime_rules();
+        let landing = self.airtime.update(flags.ground_contact, vertical_velocity, angular_velocity, up_dot, &rules, context.dt);
+        if let Some(Landing::Crash { .. }) = landing {
+            let package_damage = self.difficulty.scale_damage(self.crash_package_damage);
+            self.cargo.apply_damage(package_damage);
+            // Crashes only hurt with `crash_package_damage` set, a harmless one doesn't break streaks.
+            if package_damage > 0 {
+                publish(context.plugins, GameplayEvent::Damage { player: context.handle, player_damage: 0, package_damage });
+            }
+        }
+        let terrain = self.terrain.update(std::mem::take(&mut flags.terrain_contacts), context.dt);
+        if let Some(visual) = context.scene.graph.try_get_mut(self.lingering_visual) {
+            visual.set_visibility(!self.terrain.lingering().is_empty());
+        }
+        self.cargo.tick_deadlines(context.dt);
+        self.cargo.remove_destroyed();
+        self.update_package_visuals(&mut context.scene.graph);
         if flags.reverse_direction {
-            self.direction = match self.direction {
-                Left => Right,
-                Right => Left
-            };
-            self.rotate_player(&mut context.scene.graph, self.player_model);
+            self.set_direction(self.direction.opposite(), context.handle, &mut context.scene.graph);
+            publish(context.plugins, GameplayEvent::DirectionChange {
+                player: context.handle,
+                direction: self.direction.clone(),
+            });
+        }
+        if flags.platform != self.platform {
+            self.platform = flags.platform;
+            if flags.platform.is_some() {
+                context.message_sender.send_to_target(flags.platform, PlatformTriggerMessage { contact: true });
+            } else {
+                // Leaving a platform keeps its momentum.
+                self.carried_velocity = Vector3::default();
+            }
         }
         if let Some(rigid_body) = context.scene.graph[context.handle].cast_mut::<RigidBody>() {
-            let vel = rigid_body.lin_vel();
-            if flags.reverse_direction {
-                rigid_body.set_lin_vel(Vector3::new(0.0, vel.y, vel.z));
+            // Pick up any change in the platform's velocity since the last frame, so the player rides
+            // along instead of sliding off.
+            if flags.platform.is_some() {
+                let carried_change = flags.platform_velocity - self.carried_velocity;
+                self.carried_velocity = flags.platform_velocity;
+                rigid_body.set_lin_vel(rigid_body.lin_vel() + carried_change);
+            }
+            let carried = self.carried_velocity;
+            let vel = rigid_body.lin_vel() - carried;
+            // A released jump falls fast, a launch keeps going up until it runs out of speed.
+            let launched = self.airtime.is_launched() && vel.y > 0.0;
+            if !flags.ground_contact && !self.jump_held && !launched {
+                rigid_body.set_lin_vel(Vector3::new(vel.x + carried.x, -self.jump_force*self.fall_multiplier, 0.0));
             }
-            if !flags.ground_contact && !self.jump_held{
-                rigid_body.set_lin_vel(Vector3::new(vel.x, -self.jump_force*2.0, 0.0));
+            if terrain.lateral_force != Vector3::default() {
+                rigid_body.apply_force(terrain.lateral_force);
             }
-            if vel.x.abs() == self.max_speed + flags.terrain_effects.1 {
+            let max_speed = self.max_speed
+                + terrain.max_speed
+                + self.power_ups.strength(CollectibleEffect::SpeedBoost).unwrap_or(0.0);
+            if vel.x.abs() == max_speed {
                 return;
             }
-            if vel.x.abs() > self.max_speed + flags.terrain_effects.1 {
-                rigid_body.set_lin_vel(Vector3::new(match self.direction {
-                    Left => self.max_speed + flags.terrain_effects.1,
-                    Right => -self.max_speed - flags.terrain_effects.1
-                }, vel.y, vel.z));
+            if vel.x.abs() > max_speed {
+                rigid_body.set_lin_vel(Vector3::new(carried.x + match self.direction {
+                    Left => max_speed,
+                    Right => -max_speed
+                }, vel.y + carried.y, vel.z));
                 return;
             }
             if flags.ground_contact {
+                let mut force = self.accel_force + terrain.accel;
+                if self.actual_battery_charge <= 0.0 {
+                    force *= self.empty_battery_accel;
+                }
+                self.actual_battery_charge = (self.actual_battery_charge - force.abs() * self.battery_drain * context.dt)
+                    .max(0.0);
                 rigid_body.apply_force(Vector3::new(match self.direction {
-                    Left => self.accel_force + flags.terrain_effects.0,
-                    Right => -self.accel_force - flags.terrain_effects.0,
+                    Left => force,
+                    Right => -force,
                 }, 0.0, 0.0));
             }
             
         }
     }
 
-    fn on_message(&mut self, message: &mut dyn ScriptMessagePayload, _ctx: &mut ScriptMessageContext) {
+    fn on_message(&mut self, message: &mut dyn ScriptMessagePayload, ctx: &mut ScriptMessageContext) {
         if let Some(DamageMessage{player_damage, package_damage}) = message.downcast_ref::<DamageMessage>() {
             Log::info("got message");
             Log::info(format!("{} {}", package_damage, player_damage));
-            self.actual_package_health -= package_damage;
-            self.actual_player_health -= player_damage;
+            if self.power_ups.is_active(CollectibleEffect::Shield) {
+                return;
+            }
+            let (player_damage, package_damage) = (self.difficulty.scale_damage(*player_damage), self.difficulty.scale_damage(*package_damage));
+            self.cargo.apply_damage(package_damage);
+            self.actual_player_health = self.actual_player_health.saturating_sub(player_damage);
+            if player_damage > 0 || package_damage > 0 {
+                publish(ctx.plugins, GameplayEvent::Damage { player: ctx.handle, player_damage, package_damage });
+            }
 
         }
+        if let Some(caught) = message.downcast_ref::<PursuerCaughtMessage>() {
+            if self.power_ups.is_active(CollectibleEffect::Shield) {
+                return;
+            }
+            let player_damage = self.difficulty.scale_damage(caught.player_damage);
+            let mut package_damage = 0;
+            if caught.steal_package {
+                self.cargo.take_top();
+            } else {
+                package_damage = self.difficulty.scale_damage(caught.package_damage);
+                self.cargo.apply_damage(package_damage);
+            }
+            self.actual_player_health = self.actual_player_health.saturating_sub(player_damage);
+            if player_damage > 0 || package_damage > 0 {
+                publish(ctx.plugins, GameplayEvent::Damage { player: ctx.handle, player_damage, package_damage });
+            }
+        }
+        if let Some(CollectibleMessage{effect, amount, duration}) = message.downcast_ref::<CollectibleMessage>() {
+            match effect {
+                CollectibleEffect::RestoreCharge => {
+                    self.actual_battery_charge = (self.actual_battery_charge + amount).min(self.battery_capacity);
+                }
+                CollectibleEffect::RepairPackage => {
+                    self.cargo.repair(amount.max(0.0) as u8, self.difficulty.scale_package_health(self.package_health));
+                }
+                _ => self.power_ups.activate(effect.clone(), *amount, *duration),
+            }
+        }
+        if let Some(LaunchMessage{impulse, reset_vertical_velocity, kind}) = message.downcast_ref::<LaunchMessage>() {
+            if let Some(rigid_body) = ctx.scene.graph[ctx.handle].cast_mut::<RigidBody>() {
+                if *reset_vertical_velocity {
+                    let vel = rigid_body.lin_vel();
+                    rigid_body.set_lin_vel(Vector3::new(vel.x, 0.0, vel.z));
+                }
+                rigid_body.apply_impulse(*impulse);
+            }
+            self.airtime.launch(*kind);
+        }
+        if let Some(ReverseMessage{direction, bounce_impulse, bounce_lift}) = message.downcast_ref::<ReverseMessage>() {
+            let direction = direction.clone().unwrap_or_else(|| self.direction.opposite());
+            if direction != self.direction {
+                self.set_direction(direction, ctx.handle, &mut ctx.scene.graph);
+                publish(ctx.plugins, GameplayEvent::DirectionChange {
+                    player: ctx.handle,
+                    direction: self.direction.clone(),
+                });
+            }
+            if let Some(rigid_body) = ctx.scene.graph[ctx.handle].cast_mut::<RigidBody>() {
+                rigid_body.apply_impulse(Vector3::new(self.direction.sign() * bounce_impulse, *bounce_lift, 0.0));
+            }
+        }
     }
 
     fn id(&self) -> Uuid {
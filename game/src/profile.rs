@@ -0,0 +1,61 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use fyrox::core::log::Log;
+use serde::{Deserialize, Serialize};
+use crate::achievements::AchievementProgress;
+use crate::daily::DailyResult;
+use crate::difficulty::{Difficulty, DifficultyModifiers};
+use crate::time_trial::LevelRecord;
+
+pub const SAVE_DIR: &str = "saves";
+
+/// Player choices that persist between sessions, stored as RON in the save folder.
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+#[serde(default)]
+pub struct Profile {
+    pub difficulty: Difficulty,
+    pub custom_difficulty: DifficultyModifiers,
+    /// Best time trial results by level name.
+    pub records: BTreeMap<String, LevelRecord>,
+    /// Scored daily challenge attempts by date.
+    pub daily: BTreeMap<String, DailyResult>,
+    /// Last name entered for the leaderboard.
+    pub player_name: String,
+    /// Achievement progress by id.
+    pub achievements: BTreeMap<String, AchievementProgress>,
+}
+
+impl Profile {
+    pub fn default_path() -> PathBuf {
+        Path::new(SAVE_DIR).join("profile.ron")
+    }
+
+    /// Falls back to the defaults when there is no profile yet or it can't be read.
+    pub fn load(path: &Path) -> Self {
+        match std::fs::read_to_string(path) {
+            Ok(text) => ron::from_str(&text).unwrap_or_else(|err| {
+                Log::err(format!("Unable to parse profile {}: {}", path.display(), err));
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn save(&self, path: &Path) {
+        let result = ron::ser::to_string_pretty(self, Default::default())
+            .map_err(|err| err.to_string())
+            .and_then(|text| {
+                if let Some(dir) = path.parent() {
+                    std::fs::create_dir_all(dir).map_err(|err| err.to_string())?;
+                }
+                std::fs::write(path, text).map_err(|err| err.to_string())
+            });
+        if let Err(err) = result {
+            Log::err(format!("Unable to save profile {}: {}", path.display(), err));
+        }
+    }
+
+    pub fn difficulty_modifiers(&self) -> DifficultyModifiers {
+        self.difficulty.modifiers(&self.custom_difficulty)
+    }
+}
@@ -0,0 +1,214 @@
+use std::collections::VecDeque;
+use fyrox::{
+    core::{uuid::{Uuid, uuid}, visitor::prelude::*, reflect::prelude::*, TypeUuidProvider},
+    event::Event, impl_component_provider,
+    script::{ScriptContext, ScriptDeinitContext, ScriptTrait},
+};
+use fyrox::core::algebra::Vector3;
+use fyrox::core::pool::Handle;
+use fyrox::scene::graph::Graph;
+use fyrox::scene::node::Node;
+use fyrox::scene::rigidbody::RigidBody;
+use fyrox::script::{ScriptMessageContext, ScriptMessagePayload};
+use crate::destructible::LevelResetMessage;
+use crate::player_controller::PlayerController;
+
+/// Sent to the player when the pursuer catches up with them.
+#[derive(Debug, Clone)]
+pub struct PursuerCaughtMessage {
+    /// Takes the top package instead of damaging the cargo.
+    pub steal_package: bool,
+    pub player_damage: u8,
+    pub package_damage: u8,
+}
+
+/// A drone or rival courier that follows the path the player took, some seconds behind.
+#[derive(Visit, Reflect, Debug, Clone)]
+pub struct Pursuer {
+    pub active: bool,
+    /// Seconds after the level starts before the chase begins.
+    pub start_delay: f32,
+    /// How far behind the player the pursuer starts, in seconds of the player's path.
+    pub delay: f32,
+    /// The pursuer never falls further behind than this, in seconds.
+    pub max_delay: f32,
+    pub speed: f32,
+    /// Speed used while the player moves slower than `slow_player_speed`.
+    pub catch_up_speed: f32,
+    pub slow_player_speed: f32,
+    pub catch_radius: f32,
+    pub steal_package: bool,
+    pub player_damage: u8,
+    pub package_damage: u8,
+    /// Seconds the pursuer hovers in place after a catch, giving the player a head start.
+    pub recover_time: f32,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    trail: VecDeque<(f32, Vector3<f32>)>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    time: f32,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    recover_timer: f32,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    distance: Option<f32>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    start_position: Vector3<f32>,
+}
+
+impl Default for Pursuer {
+    fn default() -> Self {
+        Self {
+            active: true,
+            start_delay: 3.0,
+            delay: 3.0,
+            max_delay: 6.0,
+            speed: 4.0,
+            catch_up_speed: 6.0,
+            slow_player_speed: 1.0,
+            catch_radius: 0.5,
+            steal_package: false,
+            player_damage: 0,
+            package_damage: 20,
+            recover_time: 3.0,
+            trail: Default::default(),
+            time: 0.0,
+            recover_timer: 0.0,
+            distance: None,
+            start_position: Default::default(),
+        }
+    }
+}
+
+impl Pursuer {
+    /// Distance to the player along their path, `None` while the chase hasn't started.
+    pub fn distance(&self) -> Option<f32> {
+        self.distance
+    }
+
+    fn reset(&mut self, handle: Handle<Node>, graph: &mut Graph) {
+        self.trail.clear();
+        self.time = 0.0;
+        self.recover_timer = 0.0;
+        self.distance = None;
+        graph[handle].local_transform_mut().set_position(self.start_position);
+    }
+
+    fn path_distance(&self, from: Vector3<f32>) -> f32 {
+        let mut previous = from;
+        let mut distance = 0.0;
+        for (_, point) in self.trail.iter() {
+            distance += (point - previous).norm();
+            previous = *point;
+        }
+        distance
+    }
+
+    /// Moves up to `step` along the trail, dropping the points it passes.
+    fn follow_trail(&mut self, mut position: Vector3<f32>, mut step: f32) -> Vector3<f32> {
+        while let Some((_, point)) = self.trail.front().copied() {
+            let offset = point - position;
+            let length = offset.norm();
+            if length > step {
+                return position + offset.scale(step / length);
+            }
+            position = point;
+            step -= length;
+            self.trail.pop_front();
+        }
+        position
+    }
+}
+
+impl_component_provider!(Pursuer);
+
+impl TypeUuidProvider for Pursuer {
+    fn type_uuid() -> Uuid {
+        uuid!("c84f1d27-3a95-4e6b-b0d8-5f2e7a9c1346")
+    }
+}
+
+impl ScriptTrait for Pursuer {
+    fn on_init(&mut self, _context: &mut ScriptContext) {}
+
+    fn on_start(&mut self, context: &mut ScriptContext) {
+        context.message_dispatcher.subscribe_to::<LevelResetMessage>(context.handle);
+        self.start_position = **context.scene.graph[context.handle].local_transform().position();
+    }
+
+    fn on_deinit(&mut self, _context: &mut ScriptDeinitContext) {}
+
+    fn on_os_event(&mut self, _event: &Event<()>, _context: &mut ScriptContext) {}
+
+    fn on_update(&mut self, context: &mut ScriptContext) {
+        if !self.active {
+            return;
+        }
+        let graph = &mut context.scene.graph;
+        let (player, player_position, player_speed) = match graph.find_from_root(&mut |n| n.has_script::<PlayerController>()) {
+            Some((handle, node)) => (
+                handle,
+                node.global_position(),
+                node.cast::<RigidBody>().map_or(0.0, |b| b.lin_vel().norm()),
+            ),
+            None => return,
+        };
+
+        self.time += context.dt;
+        self.trail.push_back((self.time, player_position));
+        if self.time < self.start_delay {
+            return;
+        }
+
+        let mut position = graph[context.handle].global_position();
+        if self.distance.is_none() {
+            // Appear on the player's path `delay` seconds behind them.
+            let start = self.time - self.delay;
+            while self.trail.len() > 1 && self.trail[0].0 < start {
+                self.trail.pop_front();
+            }
+            position = self.trail[0].1;
+        }
+        // Don't let a slow pursuer drift out of the chase, unless it is still recovering from a catch.
+        let recovering = self.recover_timer > 0.0;
+        let cutoff = self.time - self.max_delay;
+        while self.trail.len() > 1 && self.trail[0].0 < cutoff {
+            self.trail.pop_front();
+            if !recovering {
+                position = self.trail[0].1;
+            }
+        }
+
+        if recovering {
+            self.recover_timer -= context.dt;
+        } else {
+            let speed = if player_speed < self.slow_player_speed { self.catch_up_speed } else { self.speed };
+            position = self.follow_trail(position, speed * context.dt);
+        }
+        graph[context.handle].local_transform_mut().set_position(position);
+
+        self.distance = Some(self.path_distance(position));
+        if self.recover_timer <= 0.0 && (player_position - position).norm() <= self.catch_radius {
+            self.recover_timer = self.recover_time;
+            context.message_sender.send_to_target(player, PursuerCaughtMessage {
+                steal_package: self.steal_package,
+                player_damage: self.player_damage,
+                package_damage: self.package_damage,
+            });
+        }
+    }
+
+    fn on_message(&mut self, message: &mut dyn ScriptMessagePayload, ctx: &mut ScriptMessageContext) {
+        if message.downcast_ref::<LevelResetMessage>().is_some() {
+            self.reset(ctx.handle, &mut ctx.scene.graph);
+        }
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+}
@@ -0,0 +1,141 @@
+use fyrox::core::pool::Handle;
+use fyrox::gui::button::{ButtonBuilder, ButtonMessage};
+use fyrox::gui::message::{MessageDirection, UiMessage};
+use fyrox::gui::stack_panel::StackPanelBuilder;
+use fyrox::gui::text::{TextBuilder, TextMessage};
+use fyrox::gui::text_box::TextBoxBuilder;
+use fyrox::gui::widget::{WidgetBuilder, WidgetMessage};
+use fyrox::gui::{BuildContext, HorizontalAlignment, Thickness, UiNode, UserInterface, VerticalAlignment};
+use crate::time_trial::RunResult;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResultsAction {
+    Retry,
+    Close,
+    /// Enter the run on the leaderboard under this name.
+    SubmitScore(String),
+}
+
+/// Shown when the level is completed.
+pub struct ResultsScreen {
+    root: Handle<UiNode>,
+    text: Handle<UiNode>,
+    name_box: Handle<UiNode>,
+    submit: Handle<UiNode>,
+    rank: Handle<UiNode>,
+    retry: Handle<UiNode>,
+    close: Handle<UiNode>,
+    name: String,
+}
+
+fn make_button(ctx: &mut BuildContext, text: &str) -> Handle<UiNode> {
+    ButtonBuilder::new(WidgetBuilder::new()
+        .with_width(200.0)
+        .with_height(30.0)
+        .with_margin(Thickness::uniform(2.0)))
+        .with_text(text)
+        .build(ctx)
+}
+
+impl ResultsScreen {
+    pub fn new(ctx: &mut BuildContext) -> Self {
+        let text = TextBuilder::new(WidgetBuilder::new()
+            .with_margin(Thickness::uniform(2.0)))
+            .build(ctx);
+        let name_box = TextBoxBuilder::new(WidgetBuilder::new()
+            .with_width(200.0)
+            .with_height(30.0)
+            .with_margin(Thickness::uniform(2.0)))
+            .build(ctx);
+        let submit = make_button(ctx, "Submit score");
+        let rank = TextBuilder::new(WidgetBuilder::new()
+            .with_margin(Thickness::uniform(2.0)))
+            .build(ctx);
+        let retry = make_button(ctx, "Retry");
+        let close = make_button(ctx, "Close");
+        let root = StackPanelBuilder::new(WidgetBuilder::new()
+            .with_visibility(false)
+            .with_horizontal_alignment(HorizontalAlignment::Center)
+            .with_vertical_alignment(VerticalAlignment::Center)
+            .with_child(text)
+            .with_child(name_box)
+            .with_child(submit)
+            .with_child(rank)
+            .with_child(retry)
+            .with_child(close))
+            .build(ctx);
+
+        Self { root, text, name_box, submit, rank, retry, close, name: Default::default() }
+    }
+
+    pub fn show(&self, result: &RunResult, new_best: bool, ui: &UserInterface) {
+        let mut text = format!(
+            "Time: {:.2}s\nDeliveries: {}\nPackage health: {}/{} (+{:.2}s)\nFinal time: {:.2}s\nMedal: {}",
+            result.time,
+            result.deliveries,
+            result.package_health,
+            result.max_package_health,
+            result.penalty,
+            result.final_time(),
+            result.medal.as_ref().map_or("None", |m| m.as_ref()),
+        );
+        if new_best {
+            text.push_str("\nNew best time!");
+        }
+        ui.send_message(TextMessage::text(self.text, MessageDirection::ToWidget, text));
+        self.set_visible(true, ui);
+    }
+
+    /// Offers name entry for the leaderboard, filled in with the last name used.
+    pub fn show_score_entry(&mut self, name: &str, ui: &UserInterface) {
+        self.name = name.to_string();
+        ui.send_message(TextMessage::text(self.name_box, MessageDirection::ToWidget, self.name.clone()));
+        ui.send_message(TextMessage::text(self.rank, MessageDirection::ToWidget, String::new()));
+        self.set_score_entry_visible(true, ui);
+    }
+
+    /// Replaces name entry with where the run placed.
+    pub fn show_rank(&self, rank: Option<usize>, ui: &UserInterface) {
+        self.set_score_entry_visible(false, ui);
+        let text = match rank {
+            Some(rank) => format!("Leaderboard rank: #{}", rank + 1),
+            None => "Not enough for the leaderboard".to_string(),
+        };
+        ui.send_message(TextMessage::text(self.rank, MessageDirection::ToWidget, text));
+    }
+
+    /// For runs that don't go on a leaderboard, e.g. daily practice.
+    pub fn hide_score_entry(&self, ui: &UserInterface) {
+        self.set_score_entry_visible(false, ui);
+        ui.send_message(TextMessage::text(self.rank, MessageDirection::ToWidget, String::new()));
+    }
+
+    fn set_score_entry_visible(&self, visible: bool, ui: &UserInterface) {
+        ui.send_message(WidgetMessage::visibility(self.name_box, MessageDirection::ToWidget, visible));
+        ui.send_message(WidgetMessage::visibility(self.submit, MessageDirection::ToWidget, visible));
+    }
+
+    pub fn set_visible(&self, visible: bool, ui: &UserInterface) {
+        ui.send_message(WidgetMessage::visibility(self.root, MessageDirection::ToWidget, visible));
+    }
+
+    pub fn handle_message(&mut self, message: &UiMessage) -> Option<ResultsAction> {
+        if let Some(TextMessage::Text(text)) = message.data() {
+            if message.destination() == self.name_box && message.direction() == MessageDirection::FromWidget {
+                self.name = text.clone();
+            }
+        }
+        if let Some(ButtonMessage::Click) = message.data() {
+            if message.destination() == self.submit && !self.name.trim().is_empty() {
+                return Some(ResultsAction::SubmitScore(self.name.trim().to_string()));
+            }
+            if message.destination() == self.retry {
+                return Some(ResultsAction::Retry);
+            }
+            if message.destination() == self.close {
+                return Some(ResultsAction::Close);
+            }
+        }
+        None
+    }
+}
@@ -1,12 +1,119 @@
-
 use fyrox::{
     core::{uuid::{Uuid, uuid}, visitor::prelude::*, reflect::prelude::*, TypeUuidProvider},
     event::Event, impl_component_provider,
     script::{ScriptContext, ScriptDeinitContext, ScriptTrait},
 };
+use fyrox::core::pool::Handle;
+use fyrox::scene::node::Node;
+use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
+use crate::contacts::find_player;
+use crate::player_controller::{MoveDirection, PlayerController};
+
+#[derive(Debug, Visit, Reflect, Clone, PartialEq, AsRefStr, EnumString, EnumVariantNames)]
+pub enum ReverseTriggerMode {
+    EveryTime,
+    OneShot,
+}
+
+impl Default for ReverseTriggerMode {
+    fn default() -> Self {
+        ReverseTriggerMode::EveryTime
+    }
+}
+
+#[derive(Debug, Visit, Reflect, Clone, PartialEq, AsRefStr, EnumString, EnumVariantNames)]
+pub enum ReverseCondition {
+    Always,
+    OnlyWhenCarrying,
+    OnlyWhenEmpty,
+}
+
+impl Default for ReverseCondition {
+    fn default() -> Self {
+        ReverseCondition::Always
+    }
+}
+
+#[derive(Debug, Visit, Reflect, Clone, PartialEq, AsRefStr, EnumString, EnumVariantNames)]
+pub enum ReverseAction {
+    Toggle,
+    ForceLeft,
+    ForceRight,
+}
+
+impl Default for ReverseAction {
+    fn default() -> Self {
+        ReverseAction::Toggle
+    }
+}
+
+/// Sent to the player when they touch a reverse trigger.
+#[derive(Debug, Clone)]
+pub struct ReverseMessage {
+    /// `None` toggles the current direction.
+    pub direction: Option<MoveDirection>,
+    /// Horizontal impulse applied in the new direction.
+    pub bounce_impulse: f32,
+    /// Upward impulse applied together with the bounce.
+    pub bounce_lift: f32,
+}
 
 #[derive(Visit, Reflect, Default, Debug, Clone)]
-pub struct ReverseDirection {}
+pub struct ReverseDirection {
+    #[visit(optional)]
+    pub trigger_mode: ReverseTriggerMode,
+    #[visit(optional)]
+    pub condition: ReverseCondition,
+    #[visit(optional)]
+    pub action: ReverseAction,
+    /// Seconds before the trigger can fire again.
+    #[visit(optional)]
+    pub cooldown: f32,
+    #[visit(optional)]
+    pub bounce_impulse: f32,
+    #[visit(optional)]
+    pub bounce_lift: f32,
+    #[visit(optional)]
+    turn_around_sound: Handle<Node>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    touching: bool,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    triggered: bool,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    cooldown_timer: f32,
+}
+
+impl ReverseDirection {
+    pub fn accepts(&self, player: &PlayerController) -> bool {
+        if self.trigger_mode == ReverseTriggerMode::OneShot && self.triggered {
+            return false;
+        }
+        if self.cooldown_timer > 0.0 {
+            return false;
+        }
+        match self.condition {
+            ReverseCondition::Always => true,
+            ReverseCondition::OnlyWhenCarrying => !player.cargo.is_empty(),
+            ReverseCondition::OnlyWhenEmpty => player.cargo.is_empty(),
+        }
+    }
+
+    pub fn message(&self) -> ReverseMessage {
+        ReverseMessage {
+            direction: match self.action {
+                ReverseAction::Toggle => None,
+                ReverseAction::ForceLeft => Some(MoveDirection::Left),
+                ReverseAction::ForceRight => Some(MoveDirection::Right),
+            },
+            bounce_impulse: self.bounce_impulse,
+            bounce_lift: self.bounce_lift,
+        }
+    }
+}
 
 impl_component_provider!(ReverseDirection);
 
@@ -25,10 +132,38 @@ impl ScriptTrait for ReverseDirection {
 
     fn on_os_event(&mut self, _event: &Event<()>, _context: &mut ScriptContext) {}
 
-    fn on_update(&mut self, _context: &mut ScriptContext) {}
+    fn on_update(&mut self, context: &mut ScriptContext) {
+        self.cooldown_timer = (self.cooldown_timer - context.dt).max(0.0);
+        let graph = &mut context.scene.graph;
+        let player = match find_player(context.plugins, context.handle, graph) {
+            Some(player) => player,
+            None => {
+                self.touching = false;
+                return;
+            }
+        };
+        // A contact that lasts several frames only counts once. One that starts during the
+        // cooldown is held back until the cooldown runs out.
+        if self.touching || self.cooldown_timer > 0.0 {
+            return;
+        }
+        self.touching = true;
+
+        let accepted = graph[player]
+            .try_get_script::<PlayerController>()
+            .map_or(false, |p| self.accepts(p));
+        if accepted {
+            self.triggered = true;
+            self.cooldown_timer = self.cooldown;
+            context.message_sender.send_to_target(player, self.message());
+            if let Some(sound) = graph.try_get_mut(self.turn_around_sound) {
+                sound.as_sound_mut().stop();
+                sound.as_sound_mut().play();
+            }
+        }
+    }
 
     fn id(&self) -> Uuid {
         Self::type_uuid()
     }
 }
-    
\ No newline at end of file
@@ -0,0 +1,123 @@
+use fyrox::core::pool::Handle;
+use fyrox::gui::button::{ButtonBuilder, ButtonMessage};
+use fyrox::gui::message::{MessageDirection, UiMessage};
+use fyrox::gui::stack_panel::StackPanelBuilder;
+use fyrox::gui::text::{TextBuilder, TextMessage};
+use fyrox::gui::widget::{WidgetBuilder, WidgetMessage};
+use fyrox::gui::{BuildContext, HorizontalAlignment, Thickness, UiNode, UserInterface, VerticalAlignment};
+use crate::leaderboard::{LeaderboardFilter, LeaderboardStore};
+
+/// Leaderboard tables opened from the pause menu, filtered by level, mode and difficulty.
+pub struct ScoreboardScreen {
+    root: Handle<UiNode>,
+    filter_text: Handle<UiNode>,
+    text: Handle<UiNode>,
+    level: Handle<UiNode>,
+    mode: Handle<UiNode>,
+    difficulty: Handle<UiNode>,
+    close: Handle<UiNode>,
+    filter: LeaderboardFilter,
+}
+
+fn make_button(ctx: &mut BuildContext, text: &str) -> Handle<UiNode> {
+    ButtonBuilder::new(WidgetBuilder::new()
+        .with_width(200.0)
+        .with_height(30.0)
+        .with_margin(Thickness::uniform(2.0)))
+        .with_text(text)
+        .build(ctx)
+}
+
+impl ScoreboardScreen {
+    pub fn new(ctx: &mut BuildContext) -> Self {
+        let filter_text = TextBuilder::new(WidgetBuilder::new()
+            .with_margin(Thickness::uniform(2.0)))
+            .build(ctx);
+        let text = TextBuilder::new(WidgetBuilder::new()
+            .with_margin(Thickness::uniform(2.0)))
+            .build(ctx);
+        let level = make_button(ctx, "Next level");
+        let mode = make_button(ctx, "Next mode");
+        let difficulty = make_button(ctx, "Next difficulty");
+        let close = make_button(ctx, "Close");
+        let root = StackPanelBuilder::new(WidgetBuilder::new()
+            .with_visibility(false)
+            .with_horizontal_alignment(HorizontalAlignment::Center)
+            .with_vertical_alignment(VerticalAlignment::Center)
+            .with_child(filter_text)
+            .with_child(level)
+            .with_child(mode)
+            .with_child(difficulty)
+            .with_child(text)
+            .with_child(close))
+            .build(ctx);
+
+        Self { root, filter_text, text, level, mode, difficulty, close, filter: Default::default() }
+    }
+
+    pub fn show(&self, store: &dyn LeaderboardStore, ui: &UserInterface) {
+        self.refresh(store, ui);
+        self.set_visible(true, ui);
+    }
+
+    pub fn set_visible(&self, visible: bool, ui: &UserInterface) {
+        ui.send_message(WidgetMessage::visibility(self.root, MessageDirection::ToWidget, visible));
+    }
+
+    fn refresh(&self, store: &dyn LeaderboardStore, ui: &UserInterface) {
+        let entries = store.entries(&self.filter);
+        let text = if entries.is_empty() {
+            "No scores yet".to_string()
+        } else {
+            entries
+                .iter()
+                .take(20)
+                .enumerate()
+                .map(|(rank, e)| format!(
+                    "{}. {} {} - {} {} {} - {:.2}s, {} deliveries, {}",
+                    rank + 1,
+                    e.name,
+                    e.score,
+                    e.level,
+                    e.mode.as_ref(),
+                    e.difficulty.as_ref(),
+                    e.time,
+                    e.deliveries,
+                    e.date,
+                ))
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+        ui.send_message(TextMessage::text(self.text, MessageDirection::ToWidget, text));
+
+        let filter = format!(
+            "Level: {}  Mode: {}  Difficulty: {}",
+            self.filter.level.as_deref().unwrap_or("All"),
+            self.filter.mode.as_ref().map_or("All", |m| m.as_ref()),
+            self.filter.difficulty.as_ref().map_or("All", |d| d.as_ref()),
+        );
+        ui.send_message(TextMessage::text(self.filter_text, MessageDirection::ToWidget, filter));
+    }
+
+    /// Cycles the filters on click, returns true when the screen was closed.
+    pub fn handle_message(&mut self, message: &UiMessage, store: &dyn LeaderboardStore, ui: &UserInterface) -> bool {
+        if let Some(ButtonMessage::Click) = message.data() {
+            let destination = message.destination();
+            if destination == self.close {
+                self.set_visible(false, ui);
+                return true;
+            }
+            if destination == self.level {
+                self.filter.next_level(&store.levels());
+            } else if destination == self.mode {
+                self.filter.next_mode();
+            } else if destination == self.difficulty {
+                self.filter.next_difficulty();
+            } else {
+                return false;
+            }
+            self.refresh(store, ui);
+        }
+        false
+    }
+}
@@ -1,15 +1,15 @@
-
 use fyrox::{
     core::{uuid::{Uuid, uuid}, visitor::prelude::*, reflect::prelude::*, TypeUuidProvider},
     event::Event, impl_component_provider,
     script::{ScriptContext, ScriptDeinitContext, ScriptTrait},
 };
-use fyrox::core::log::Log;
+use fyrox::core::algebra::Vector3;
 use fyrox::core::pool::Handle;
-use fyrox::scene::collider::Collider;
+use fyrox::asset::Resource;
 use fyrox::scene::graph::Graph;
 use fyrox::scene::node::Node;
-use crate::player_controller::PlayerController;
+use crate::contacts::{contact_events, ContactPhase};
+use crate::terrain_preset::TerrainPreset;
 
 #[derive(Debug, Clone)]
 pub struct DamageMessage {
@@ -17,35 +17,261 @@ pub struct DamageMessage {
     pub package_damage: u8,
 }
 
+/// Movement modifiers a terrain zone applies to the player.
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub struct TerrainModifiers {
+    pub accel: f32,
+    pub max_speed: f32,
+    pub lateral_force: Vector3<f32>,
+}
+
+impl TerrainModifiers {
+    /// Overlapping zones add up.
+    pub fn stack(self, other: TerrainModifiers) -> Self {
+        Self {
+            accel: self.accel + other.accel,
+            max_speed: self.max_speed + other.max_speed,
+            lateral_force: self.lateral_force + other.lateral_force,
+        }
+    }
+}
+
+/// An active terrain zone the player overlaps this frame.
+#[derive(Debug, Clone)]
+pub struct TerrainContact {
+    pub source: Handle<Node>,
+    pub modifiers: TerrainModifiers,
+    pub linger_time: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct LingeringEffect {
+    pub source: Handle<Node>,
+    pub modifiers: TerrainModifiers,
+    pub remaining: f32,
+}
+
+/// Tracks the zones the player is in and the effects that stay with them after leaving.
+///
+/// Stacking rules:
+/// - every zone the player is inside adds its modifiers,
+/// - a zone starts lingering once it stops applying, either because the player left or it switched off,
+/// - leaving the same zone again refreshes its lingering timer instead of stacking a second copy,
+/// - a lingering effect is suppressed while the player is back inside its source zone.
+#[derive(Default, Debug, Clone)]
+pub struct TerrainStack {
+    inside: Vec<TerrainContact>,
+    lingering: Vec<LingeringEffect>,
+}
+
+impl TerrainStack {
+    pub fn update(&mut self, contacts: Vec<TerrainContact>, dt: f32) -> TerrainModifiers {
+        for effect in self.lingering.iter_mut() {
+            effect.remaining -= dt;
+        }
+        self.lingering.retain(|e| e.remaining > 0.0);
+
+        for left in self.inside.iter().filter(|c| !contacts.iter().any(|n| n.source == c.source)) {
+            if left.linger_time <= 0.0 {
+                continue;
+            }
+            if let Some(effect) = self.lingering.iter_mut().find(|e| e.source == left.source) {
+                effect.modifiers = left.modifiers;
+                effect.remaining = left.linger_time;
+            } else {
+                self.lingering.push(LingeringEffect {
+                    source: left.source,
+                    modifiers: left.modifiers,
+                    remaining: left.linger_time,
+                });
+            }
+        }
+        self.inside = contacts;
+
+        self.combined()
+    }
+
+    pub fn combined(&self) -> TerrainModifiers {
+        let inside = self
+            .inside
+            .iter()
+            .fold(TerrainModifiers::default(), |acc, c| acc.stack(c.modifiers));
+        self.lingering
+            .iter()
+            .filter(|e| !self.inside.iter().any(|c| c.source == e.source))
+            .fold(inside, |acc, e| acc.stack(e.modifiers))
+    }
+
+    pub fn lingering(&self) -> &[LingeringEffect] {
+        &self.lingering
+    }
+
+    pub fn clear(&mut self) {
+        self.inside.clear();
+        self.lingering.clear();
+    }
+}
+
+/// Fields of a `TerrainEffect` that keep their own value instead of the preset's.
+#[derive(Visit, Reflect, Default, Debug, Clone)]
+pub struct TerrainOverrides {
+    pub accel_modifier: bool,
+    pub max_speed_mod: bool,
+    pub player_damage: bool,
+    pub package_damage: bool,
+    pub player_damage_per_second: bool,
+    pub package_damage_per_second: bool,
+    pub linger_time: bool,
+    pub lateral_force: bool,
+}
+
 #[derive(Visit, Reflect, Default, Debug, Clone)]
 pub struct TerrainEffect {
+    /// Shared values for this hazard, fields below are only used when overridden or without a preset.
+    #[visit(optional)]
+    pub preset: Option<Resource<TerrainPreset>>,
+    #[visit(optional)]
+    pub overrides: TerrainOverrides,
+
     pub accel_modifier: f32,
     pub max_speed_mod: f32,
     pub player_damage: u8,
     pub package_damage: u8,
 
+    /// Damage dealt every second while the player stays inside.
+    #[visit(optional)]
+    pub player_damage_per_second: f32,
+    #[visit(optional)]
+    pub package_damage_per_second: f32,
+    /// Seconds the movement modifiers keep applying after the player leaves.
+    #[visit(optional)]
+    pub linger_time: f32,
+    /// Constant force pushing the player while inside, e.g. wind or a conveyor.
+    #[visit(optional)]
+    pub lateral_force: Vector3<f32>,
+
+    /// Seconds the zone stays on per cycle.
+    #[visit(optional)]
+    pub active_time: f32,
+    /// Seconds the zone stays off per cycle, zero keeps it on permanently.
+    #[visit(optional)]
+    pub inactive_time: f32,
+    #[visit(optional)]
+    pub schedule_offset: f32,
+    /// Shown while the zone is on.
+    #[visit(optional)]
+    active_visual: Handle<Node>,
+    /// Shown while the zone is off.
+    #[visit(optional)]
+    inactive_visual: Handle<Node>,
+    /// Shown while the player is inside a zone that deals damage over time, e.g. sparks.
+    #[visit(optional)]
+    damage_visual: Handle<Node>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    switched_off: bool,
     #[visit(skip)]
     #[reflect(hidden)]
-    hit: Vec<Handle<Node>>,
+    damage_accumulator: (f32, f32),
 }
 
 impl TerrainEffect {
-    pub fn has_player_contact(&self, handle: Handle<Node>, graph: &Graph) -> Option<Handle<Node>> {
-        if let Some(collider) = graph.try_get_of_type::<Collider>(handle) {
-            for contact in collider.intersects(&graph.physics) {
-                if contact.has_any_active_contact {
-                    let opposing_handle = if contact.collider1.eq(&handle) { contact.collider2 } else { contact.collider1 };
-                    if let Some(parent) = graph.try_get(opposing_handle).map(|n| n.parent()) {
-                        if let Some((player, _)) = graph.find_up(parent, &mut |n| {
-                            return n.script().and_then(|s|s.cast::<PlayerController>()).is_some();
-                        }) {
-                            return Some(player);
-                        }
-                    }
-                }
-            }
+    fn resolve<T>(&self, overridden: bool, local: T, from_preset: impl FnOnce(&TerrainPreset) -> T) -> T {
+        match self.preset.as_ref() {
+            Some(preset) if !overridden && preset.is_ok() => from_preset(&preset.data_ref()),
+            _ => local,
+        }
+    }
+
+    pub fn accel_modifier(&self) -> f32 {
+        self.resolve(self.overrides.accel_modifier, self.accel_modifier, |p| p.accel_modifier)
+    }
+
+    pub fn max_speed_mod(&self) -> f32 {
+        self.resolve(self.overrides.max_speed_mod, self.max_speed_mod, |p| p.max_speed_mod)
+    }
+
+    pub fn player_damage(&self) -> u8 {
+        self.resolve(self.overrides.player_damage, self.player_damage, |p| p.player_damage)
+    }
+
+    pub fn package_damage(&self) -> u8 {
+        self.resolve(self.overrides.package_damage, self.package_damage, |p| p.package_damage)
+    }
+
+    pub fn player_damage_per_second(&self) -> f32 {
+        self.resolve(self.overrides.player_damage_per_second, self.player_damage_per_second, |p| p.player_damage_per_second)
+    }
+
+    pub fn package_damage_per_second(&self) -> f32 {
+        self.resolve(self.overrides.package_damage_per_second, self.package_damage_per_second, |p| p.package_damage_per_second)
+    }
+
+    pub fn linger_time(&self) -> f32 {
+        self.resolve(self.overrides.linger_time, self.linger_time, |p| p.linger_time)
+    }
+
+    pub fn lateral_force(&self) -> Vector3<f32> {
+        self.resolve(self.overrides.lateral_force, self.lateral_force, |p| p.lateral_force())
+    }
+
+    pub fn is_active(&self) -> bool {
+        !self.switched_off
+    }
+
+    pub fn is_scheduled_active(&self, time: f32) -> bool {
+        if self.inactive_time <= 0.0 {
+            return true;
+        }
+        let period = self.active_time.max(0.0) + self.inactive_time;
+        (time + self.schedule_offset).rem_euclid(period) < self.active_time
+    }
+
+    pub fn modifiers(&self) -> TerrainModifiers {
+        TerrainModifiers {
+            accel: self.accel_modifier(),
+            max_speed: self.max_speed_mod(),
+            lateral_force: self.lateral_force(),
         }
-        None
+    }
+
+    pub fn contact(&self, source: Handle<Node>) -> TerrainContact {
+        TerrainContact {
+            source,
+            modifiers: self.modifiers(),
+            linger_time: self.linger_time(),
+        }
+    }
+
+    fn set_active(&mut self, active: bool, graph: &mut Graph) {
+        self.switched_off = !active;
+        if let Some(visual) = graph.try_get_mut(self.active_visual) {
+            visual.set_visibility(active);
+        }
+        if let Some(visual) = graph.try_get_mut(self.inactive_visual) {
+            visual.set_visibility(!active);
+        }
+        if !active {
+            self.set_damaging(false, graph);
+        }
+    }
+
+    fn set_damaging(&self, damaging: bool, graph: &mut Graph) {
+        let deals_damage = self.player_damage_per_second() > 0.0 || self.package_damage_per_second() > 0.0;
+        if let Some(visual) = graph.try_get_mut(self.damage_visual) {
+            visual.set_visibility(damaging && deals_damage);
+        }
+    }
+
+    /// Accumulates damage over time and returns the whole points that are due.
+    fn take_damage_over_time(&mut self, dt: f32) -> (u8, u8) {
+        self.damage_accumulator.0 += self.player_damage_per_second() * dt;
+        self.damage_accumulator.1 += self.package_damage_per_second() * dt;
+        let due = (self.damage_accumulator.0.floor(), self.damage_accumulator.1.floor());
+        self.damage_accumulator.0 -= due.0;
+        self.damage_accumulator.1 -= due.1;
+        (due.0.min(u8::MAX as f32) as u8, due.1.min(u8::MAX as f32) as u8)
     }
 }
 
@@ -60,26 +286,56 @@ impl TypeUuidProvider for TerrainEffect {
 impl ScriptTrait for TerrainEffect {
     fn on_init(&mut self, _context: &mut ScriptContext) {}
 
-    fn on_start(&mut self, _context: &mut ScriptContext) {}
+    fn on_start(&mut self, context: &mut ScriptContext) {
+        let active = self.is_scheduled_active(context.elapsed_time);
+        self.set_active(active, &mut context.scene.graph);
+        self.set_damaging(false, &mut context.scene.graph);
+    }
 
     fn on_deinit(&mut self, _context: &mut ScriptDeinitContext) {}
 
     fn on_os_event(&mut self, _event: &Event<()>, _context: &mut ScriptContext) {}
 
     fn on_update(&mut self, context: &mut ScriptContext) {
-        let graph = &mut context.scene.graph;
-        if let Some(player) = self.has_player_contact(context.handle, &graph) {
-            Log::info("has player contact");
-            if !self.hit.contains(&player) {
-                Log::info("not seen");
-                self.hit.push(player);
+        let active = self.is_scheduled_active(context.elapsed_time);
+        // A zone switching on hits whoever is already standing in it, like walking in would.
+        let switched_on = active && !self.is_active();
+        if active != self.is_active() {
+            self.set_active(active, &mut context.scene.graph);
+        }
+        if !active {
+            self.damage_accumulator = (0.0, 0.0);
+            return;
+        }
+
+        let mut inside = false;
+        for event in contact_events(context.plugins, context.handle) {
+            match event.phase {
+                ContactPhase::Exit => {
+                    self.damage_accumulator = (0.0, 0.0);
+                    self.set_damaging(false, &mut context.scene.graph);
+                    continue;
+                }
+                ContactPhase::Stay if !switched_on => (),
+                _ => {
+                    self.set_damaging(true, &mut context.scene.graph);
+                    context.message_sender.send_global(DamageMessage {
+                        player_damage: self.player_damage(),
+                        package_damage: self.package_damage(),
+                    });
+                }
+            }
+            inside = true;
+        }
+        // Damage over time is per second in the zone, not per collider touching it.
+        if inside {
+            let (player_damage, package_damage) = self.take_damage_over_time(context.dt);
+            if player_damage > 0 || package_damage > 0 {
                 context.message_sender.send_global(DamageMessage {
-                    player_damage: self.player_damage,
-                    package_damage: self.package_damage,
+                    player_damage,
+                    package_damage,
                 });
             }
-        } else {
-            self.hit.clear();
         }
     }
 
@@ -87,4 +343,3 @@ impl ScriptTrait for TerrainEffect {
         Self::type_uuid()
     }
 }
-    
\ No newline at end of file
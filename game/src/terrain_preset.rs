@@ -0,0 +1,136 @@
+use std::{
+    any::Any,
+    borrow::Cow,
+    path::{Path, PathBuf},
+};
+use fyrox::{
+    core::{uuid::{Uuid, uuid}, visitor::prelude::*, reflect::prelude::*, TypeUuidProvider, io},
+};
+use fyrox::core::algebra::Vector3;
+use fyrox::core::log::Log;
+use fyrox::asset::{
+    event::ResourceEventBroadcaster,
+    loader::{BoxedLoaderFuture, ResourceLoader},
+    untyped::UntypedResource,
+    ResourceData,
+};
+use serde::{Deserialize, Serialize};
+
+/// Shared hazard values referenced by `TerrainEffect`s, stored as RON in `*.terrain` files.
+/// Scenes only store the path, the values are always read from the file. The editor's Terrain
+/// Preset window edits the preset of the selected zone and writes it back to the file.
+#[derive(Visit, Reflect, Serialize, Deserialize, Default, Debug, Clone)]
+pub struct TerrainPreset {
+    #[visit(skip)]
+    pub accel_modifier: f32,
+    #[visit(skip)]
+    pub max_speed_mod: f32,
+    #[visit(skip)]
+    pub player_damage: u8,
+    #[visit(skip)]
+    pub package_damage: u8,
+    #[serde(default)]
+    #[visit(skip)]
+    pub player_damage_per_second: f32,
+    #[serde(default)]
+    #[visit(skip)]
+    pub package_damage_per_second: f32,
+    #[serde(default)]
+    #[visit(skip)]
+    pub linger_time: f32,
+    #[serde(default)]
+    #[visit(skip)]
+    pub lateral_force: [f32; 3],
+
+    #[serde(skip)]
+    #[reflect(hidden)]
+    path: PathBuf,
+}
+
+impl TerrainPreset {
+    pub fn from_ron(text: &str) -> Result<Self, ron::error::SpannedError> {
+        ron::from_str(text)
+    }
+
+    pub fn to_ron(&self) -> Result<String, ron::Error> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+    }
+
+    pub fn lateral_force(&self) -> Vector3<f32> {
+        Vector3::from(self.lateral_force)
+    }
+}
+
+impl TypeUuidProvider for TerrainPreset {
+    fn type_uuid() -> Uuid {
+        uuid!("9a3f1c6e-7b2d-4e58-a0c4-2d8e6f1b3a57")
+    }
+}
+
+impl ResourceData for TerrainPreset {
+    fn path(&self) -> Cow<'_, Path> {
+        Cow::Borrowed(&self.path)
+    }
+
+    fn set_path(&mut self, path: PathBuf) {
+        self.path = path;
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn type_uuid(&self) -> Uuid {
+        <Self as TypeUuidProvider>::type_uuid()
+    }
+}
+
+/// Lets the resource manager load and hot-reload `*.terrain` files.
+pub struct TerrainPresetLoader;
+
+impl ResourceLoader for TerrainPresetLoader {
+    fn extensions(&self) -> &[&str] {
+        &["terrain"]
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn load(
+        &self,
+        resource: UntypedResource,
+        event_broadcaster: ResourceEventBroadcaster,
+        reload: bool,
+    ) -> BoxedLoaderFuture {
+        Box::pin(async move {
+            let path = resource.path().to_path_buf();
+            match io::load_file(&path).await {
+                Ok(bytes) => match TerrainPreset::from_ron(&String::from_utf8_lossy(&bytes)) {
+                    Ok(mut preset) => {
+                        preset.set_path(path);
+                        resource.commit_ok(preset);
+                        event_broadcaster.broadcast_loaded_or_reloaded(resource, reload);
+                    }
+                    Err(err) => {
+                        Log::err(format!("Unable to parse terrain preset {}: {}", path.display(), err));
+                        resource.commit_error(path, err);
+                    }
+                },
+                Err(err) => resource.commit_error(path, err),
+            }
+        })
+    }
+}
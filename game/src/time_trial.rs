@@ -0,0 +1,139 @@
+use fyrox::{
+    core::{uuid::{Uuid, uuid}, visitor::prelude::*, reflect::prelude::*, TypeUuidProvider},
+    event::Event, impl_component_provider,
+    script::{ScriptContext, ScriptDeinitContext, ScriptTrait},
+};
+use serde::{Deserialize, Serialize};
+use strum_macros::AsRefStr;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, AsRefStr)]
+pub enum Medal {
+    Bronze,
+    Silver,
+    Gold,
+}
+
+/// Per-level settings for the time trial, put one on any node of the level.
+#[derive(Visit, Reflect, Default, Debug, Clone)]
+pub struct LevelInfo {
+    /// Par times in seconds, zero leaves the medal out.
+    pub bronze_time: f32,
+    pub silver_time: f32,
+    pub gold_time: f32,
+    /// Seconds added to the time for every point of health the delivered packages lost.
+    pub damage_penalty: f32,
+}
+
+impl LevelInfo {
+    pub fn medal_for(&self, time: f32) -> Option<Medal> {
+        [(Medal::Gold, self.gold_time), (Medal::Silver, self.silver_time), (Medal::Bronze, self.bronze_time)]
+            .into_iter()
+            .find(|(_, par)| *par > 0.0 && time <= *par)
+            .map(|(medal, _)| medal)
+    }
+}
+
+impl_component_provider!(LevelInfo);
+
+impl TypeUuidProvider for LevelInfo {
+    fn type_uuid() -> Uuid {
+        uuid!("93b7e0d5-2c4a-4f81-a6e9-0d58c3b71f24")
+    }
+}
+
+impl ScriptTrait for LevelInfo {
+    fn on_init(&mut self, _context: &mut ScriptContext) {}
+
+    fn on_start(&mut self, _context: &mut ScriptContext) {}
+
+    fn on_deinit(&mut self, _context: &mut ScriptDeinitContext) {}
+
+    fn on_os_event(&mut self, _event: &Event<()>, _context: &mut ScriptContext) {}
+
+    fn on_update(&mut self, _context: &mut ScriptContext) {}
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+}
+
+/// Runs from the player's first movement until the level is completed.
+#[derive(Default, Debug, Clone)]
+pub struct LevelTimer {
+    time: f32,
+    started: bool,
+    stopped: bool,
+}
+
+impl LevelTimer {
+    pub fn update(&mut self, dt: f32, moving: bool) {
+        if !self.started && moving {
+            self.started = true;
+        }
+        if self.is_running() {
+            self.time += dt;
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.started && !self.stopped
+    }
+
+    pub fn stop(&mut self) {
+        self.stopped = true;
+    }
+
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+}
+
+/// How a completed run went, shown on the results screen.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct RunResult {
+    pub time: f32,
+    pub deliveries: usize,
+    pub package_health: u32,
+    pub max_package_health: u32,
+    /// Seconds added for damaged packages.
+    pub penalty: f32,
+    pub medal: Option<Medal>,
+}
+
+impl RunResult {
+    pub fn new(time: f32, deliveries: usize, package_health: u32, max_package_health: u32, level: &LevelInfo) -> Self {
+        let penalty = max_package_health.saturating_sub(package_health) as f32 * level.damage_penalty;
+        Self {
+            time,
+            deliveries,
+            package_health,
+            max_package_health,
+            penalty,
+            medal: level.medal_for(time + penalty),
+        }
+    }
+
+    pub fn final_time(&self) -> f32 {
+        self.time + self.penalty
+    }
+}
+
+/// Best result on a level, kept in the profile.
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct LevelRecord {
+    pub best_time: Option<f32>,
+    pub medal: Option<Medal>,
+}
+
+impl LevelRecord {
+    /// Keeps the better time and medal, returns true for a new best time.
+    pub fn submit(&mut self, result: &RunResult) -> bool {
+        self.medal = self.medal.max(result.medal);
+        let improved = self.best_time.map_or(true, |best| result.final_time() < best);
+        if improved {
+            self.best_time = Some(result.final_time());
+        }
+        improved
+    }
+}
@@ -0,0 +1,290 @@
+use fyrox::{
+    core::{uuid::{Uuid, uuid}, visitor::prelude::*, reflect::prelude::*, TypeUuidProvider},
+    event::Event, impl_component_provider,
+    script::{ScriptContext, ScriptDeinitContext, ScriptTrait},
+};
+use fyrox::core::algebra::{Point3, Vector3};
+use fyrox::core::pool::Handle;
+use fyrox::core::rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use fyrox::resource::model::{ModelResource, ModelResourceExtension};
+use fyrox::scene::collider::{Collider, InteractionGroups};
+use fyrox::scene::graph::Graph;
+use fyrox::scene::graph::physics::{Intersection, RayCastOptions};
+use fyrox::scene::node::Node;
+use fyrox::scene::rigidbody::{RigidBody, RigidBodyType};
+use crate::contacts::{contact_events, ContactPhase};
+use crate::airtime::LaunchKind;
+use crate::jump_pad::LaunchMessage;
+use crate::terrain_effect::DamageMessage;
+
+/// World positions of a path node's children, in child order.
+pub fn path_points(graph: &Graph, path: Handle<Node>) -> Vec<Vector3<f32>> {
+    graph
+        .try_get(path)
+        .map(|path| path.children().iter().map(|c| graph[*c].global_position()).collect())
+        .unwrap_or_default()
+}
+
+/// Speed to drive at with an obstacle `distance` ahead, slowing down linearly once it is closer
+/// than `brake_distance` and stopping at `min_gap`.
+pub fn speed_for_gap(cruise_speed: f32, distance: Option<f32>, min_gap: f32, brake_distance: f32) -> f32 {
+    match distance {
+        Some(distance) if brake_distance > min_gap => {
+            cruise_speed * ((distance - min_gap) / (brake_distance - min_gap)).clamp(0.0, 1.0)
+        }
+        Some(distance) if distance <= min_gap => 0.0,
+        _ => cruise_speed,
+    }
+}
+
+/// A car or truck that drives along a lane, brakes for whatever is in front of it and knocks the
+/// player back on contact.
+#[derive(Visit, Reflect, Default, Debug, Clone)]
+pub struct TrafficVehicle {
+    pub cruise_speed: f32,
+    /// How far ahead the vehicle looks for obstacles.
+    pub brake_distance: f32,
+    /// Distance it keeps to the obstacle in front when stopped.
+    pub min_gap: f32,
+    pub player_damage: u8,
+    pub package_damage: u8,
+    /// Impulse applied to the player along the driving direction, plus an upward part.
+    pub knockback: f32,
+    pub knockback_lift: f32,
+    collider: Handle<Node>,
+    /// Lane to follow when the vehicle is placed by hand rather than by a spawner.
+    path: Handle<Node>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    waypoints: Vec<Vector3<f32>>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    target: usize,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    finished: bool,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    ray_hits: Vec<Intersection>,
+}
+
+impl TrafficVehicle {
+    /// Sends the vehicle down a lane, used by spawners when they place or recycle it.
+    pub fn begin(&mut self, waypoints: Vec<Vector3<f32>>, cruise_speed: f32) {
+        self.waypoints = waypoints;
+        self.cruise_speed = cruise_speed;
+        self.target = 1.min(self.waypoints.len().saturating_sub(1));
+        self.finished = self.waypoints.len() < 2;
+    }
+
+    pub fn set_collider(&mut self, collider: Handle<Node>) {
+        self.collider = collider;
+    }
+
+    /// True once the vehicle reached the end of its lane and can be recycled.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    fn obstacle_distance(&mut self, origin: Vector3<f32>, direction: Vector3<f32>, graph: &Graph) -> Option<f32> {
+        self.ray_hits.clear();
+        graph.physics.cast_ray(
+            RayCastOptions {
+                ray_origin: Point3::from(origin),
+                ray_direction: direction,
+                max_len: self.brake_distance,
+                groups: InteractionGroups::default(),
+                sort_results: true,
+            },
+            &mut self.ray_hits,
+        );
+        let own_collider = self.collider;
+        self.ray_hits
+            .iter()
+            .filter(|hit| hit.collider != own_collider)
+            .filter(|hit| graph.try_get_of_type::<Collider>(hit.collider).map_or(false, |c| !c.is_sensor()))
+            .map(|hit| hit.toi)
+            .next()
+    }
+
+}
+
+impl_component_provider!(TrafficVehicle);
+
+impl TypeUuidProvider for TrafficVehicle {
+    fn type_uuid() -> Uuid {
+        uuid!("1e5c8a3b-9d74-4f26-8c01-b6a2e7d94f13")
+    }
+}
+
+impl ScriptTrait for TrafficVehicle {
+    fn on_init(&mut self, _context: &mut ScriptContext) {}
+
+    fn on_start(&mut self, context: &mut ScriptContext) {
+        // Spawners hand out their lane before the vehicle starts, don't overwrite it.
+        if self.waypoints.is_empty() {
+            let waypoints = path_points(&context.scene.graph, self.path);
+            self.begin(waypoints, self.cruise_speed);
+        }
+        if let Some(rigid_body) = context.scene.graph[context.handle].cast_mut::<RigidBody>() {
+            rigid_body.set_body_type(RigidBodyType::KinematicVelocityBased);
+        }
+    }
+
+    fn on_deinit(&mut self, _context: &mut ScriptDeinitContext) {}
+
+    fn on_os_event(&mut self, _event: &Event<()>, _context: &mut ScriptContext) {}
+
+    fn on_update(&mut self, context: &mut ScriptContext) {
+        let graph = &mut context.scene.graph;
+        let mut velocity = Vector3::default();
+        if !self.finished && context.dt > 0.0 {
+            let position = graph[context.handle].global_position();
+            let offset = self.waypoints[self.target] - position;
+            let distance = offset.norm();
+            let direction = offset.try_normalize(f32::EPSILON).unwrap_or_default();
+            let gap = self.obstacle_distance(position, direction, graph);
+            let speed = speed_for_gap(self.cruise_speed, gap, self.min_gap, self.brake_distance);
+            if distance <= speed * context.dt {
+                velocity = offset / context.dt;
+                self.target += 1;
+                self.finished = self.target >= self.waypoints.len();
+            } else {
+                velocity = direction.scale(speed);
+            }
+
+            for event in contact_events(context.plugins, self.collider) {
+                if event.phase != ContactPhase::Enter {
+                    continue;
+                }
+                let player = event.contact.player;
+                context.message_sender.send_to_target(player, DamageMessage {
+                    player_damage: self.player_damage,
+                    package_damage: self.package_damage,
+                });
+                context.message_sender.send_to_target(player, LaunchMessage {
+                    impulse: direction.scale(self.knockback) + Vector3::new(0.0, self.knockback_lift, 0.0),
+                    reset_vertical_velocity: false,
+                    kind: LaunchKind::Knockback,
+                });
+            }
+        }
+        if let Some(rigid_body) = graph[context.handle].cast_mut::<RigidBody>() {
+            rigid_body.set_lin_vel(velocity);
+        }
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+}
+
+/// Places vehicles at the start of a lane at intervals, reusing the ones that reached the end.
+/// With the same seed it produces the same sequence of vehicles.
+#[derive(Visit, Reflect, Default, Debug, Clone)]
+pub struct TrafficSpawner {
+    vehicle_prefab: Option<ModelResource>,
+    lane: Handle<Node>,
+    pub interval: f32,
+    /// Random extra delay added to each interval, up to this many seconds.
+    pub interval_jitter: f32,
+    pub cruise_speed: f32,
+    /// Random change of each vehicle's speed, up to this much either way.
+    pub speed_jitter: f32,
+    pub pool_limit: u32,
+    pub seed: u64,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    pool: Vec<Handle<Node>>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    timer: f32,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    rng: Option<ChaCha8Rng>,
+}
+
+impl TrafficSpawner {
+    fn next_interval(&mut self) -> f32 {
+        let jitter = self.interval_jitter.max(0.0);
+        let rng = self.rng.get_or_insert_with(|| ChaCha8Rng::seed_from_u64(self.seed));
+        self.interval + if jitter > 0.0 { rng.gen_range(0.0..jitter) } else { 0.0 }
+    }
+
+    fn next_speed(&mut self) -> f32 {
+        let jitter = self.speed_jitter.max(0.0);
+        let rng = self.rng.get_or_insert_with(|| ChaCha8Rng::seed_from_u64(self.seed));
+        self.cruise_speed + if jitter > 0.0 { rng.gen_range(-jitter..jitter) } else { 0.0 }
+    }
+
+    /// A finished vehicle from the pool, or a new one while the pool is below its limit.
+    fn take_vehicle(&mut self, context: &mut ScriptContext) -> Option<Handle<Node>> {
+        let graph = &context.scene.graph;
+        self.pool.retain(|h| graph.is_valid_handle(*h));
+        let finished = self.pool.iter().copied().find(|h| {
+            graph[*h].try_get_script::<TrafficVehicle>().map_or(false, |v| v.is_finished())
+        });
+        if finished.is_some() {
+            return finished;
+        }
+        if self.pool.len() >= self.pool_limit as usize {
+            return None;
+        }
+        let vehicle = self.vehicle_prefab.as_ref()?.instantiate(context.scene);
+        self.pool.push(vehicle);
+        Some(vehicle)
+    }
+
+    fn spawn(&mut self, context: &mut ScriptContext) {
+        let waypoints = path_points(&context.scene.graph, self.lane);
+        if waypoints.len() < 2 {
+            return;
+        }
+        let vehicle = match self.take_vehicle(context) {
+            Some(vehicle) => vehicle,
+            None => return,
+        };
+        let speed = self.next_speed();
+        let graph = &mut context.scene.graph;
+        graph[vehicle].local_transform_mut().set_position(waypoints[0]);
+        if let Some(script) = graph[vehicle].try_get_script_mut::<TrafficVehicle>() {
+            script.begin(waypoints, speed);
+        }
+    }
+}
+
+impl_component_provider!(TrafficSpawner);
+
+impl TypeUuidProvider for TrafficSpawner {
+    fn type_uuid() -> Uuid {
+        uuid!("7a0d3e52-6b18-4c9f-a3e7-25f8c1b06d94")
+    }
+}
+
+impl ScriptTrait for TrafficSpawner {
+    fn on_init(&mut self, _context: &mut ScriptContext) {}
+
+    fn on_start(&mut self, _context: &mut ScriptContext) {
+        self.rng = Some(ChaCha8Rng::seed_from_u64(self.seed));
+        self.timer = 0.0;
+    }
+
+    fn on_deinit(&mut self, _context: &mut ScriptDeinitContext) {}
+
+    fn on_os_event(&mut self, _event: &Event<()>, _context: &mut ScriptContext) {}
+
+    fn on_update(&mut self, context: &mut ScriptContext) {
+        self.timer -= context.dt;
+        if self.timer <= 0.0 {
+            self.spawn(context);
+            self.timer = self.next_interval();
+        }
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+}
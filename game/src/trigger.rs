@@ -0,0 +1,257 @@
+use fyrox::{
+    core::{uuid::{Uuid, uuid}, visitor::prelude::*, reflect::prelude::*, TypeUuidProvider},
+    event::Event, impl_component_provider,
+    script::{ScriptContext, ScriptDeinitContext, ScriptTrait},
+};
+use fyrox::core::pool::Handle;
+use fyrox::scene::collider::Collider;
+use fyrox::scene::graph::Graph;
+use fyrox::scene::node::Node;
+use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
+use crate::contacts::{contact_events, ContactPhase};
+use crate::moving_platform::PlatformTriggerMessage;
+use crate::player_controller::PlayerController;
+
+fn player_of(collider: Handle<Node>, graph: &Graph) -> Option<Handle<Node>> {
+    let parent = graph.try_get(collider).map(|n| n.parent())?;
+    graph
+        .find_up(parent, &mut |n| n.script().and_then(|s| s.cast::<PlayerController>()).is_some())
+        .map(|(player, _)| player)
+}
+
+/// The player overlapping the given sensor collider, if any.
+pub fn player_in_sensor(handle: Handle<Node>, graph: &Graph) -> Option<Handle<Node>> {
+    let collider = graph.try_get_of_type::<Collider>(handle)?;
+    collider
+        .intersects(&graph.physics)
+        .filter(|contact| contact.has_any_active_contact)
+        .find_map(|contact| {
+            let opposing_handle = if contact.collider1.eq(&handle) { contact.collider2 } else { contact.collider1 };
+            player_of(opposing_handle, graph)
+        })
+}
+
+/// The player physically touching the given solid collider, if any.
+pub fn player_touching(handle: Handle<Node>, graph: &Graph) -> Option<Handle<Node>> {
+    let collider = graph.try_get_of_type::<Collider>(handle)?;
+    collider
+        .contacts(&graph.physics)
+        .filter(|contact| contact.has_any_active_contact)
+        .find_map(|contact| {
+            let opposing_handle = if contact.collider1.eq(&handle) { contact.collider2 } else { contact.collider1 };
+            player_of(opposing_handle, graph)
+        })
+}
+
+#[derive(Debug, Visit, Reflect, Clone, PartialEq, AsRefStr, EnumString, EnumVariantNames)]
+pub enum TriggerEvent {
+    Enter,
+    /// Repeats every `stay_interval` seconds while the player is inside.
+    Stay,
+    Exit,
+    /// The timer started by a `StartTimer` action ran out.
+    TimerElapsed,
+}
+
+impl Default for TriggerEvent {
+    fn default() -> Self {
+        TriggerEvent::Enter
+    }
+}
+
+#[derive(Debug, Visit, Reflect, Clone, PartialEq, AsRefStr, EnumString, EnumVariantNames)]
+pub enum TriggerActionKind {
+    Enable,
+    Disable,
+    Toggle,
+    PlaySound,
+    /// Shows `text` on the HUD for `duration` seconds.
+    ShowMessage,
+    /// Counts down `duration` seconds on the HUD, labelled with `text`, then fires `TimerElapsed`.
+    StartTimer,
+    /// Starts a moving platform in triggered mode.
+    MovePlatform,
+}
+
+impl Default for TriggerActionKind {
+    fn default() -> Self {
+        TriggerActionKind::Enable
+    }
+}
+
+#[derive(Visit, Reflect, Default, Debug, Clone)]
+pub struct TriggerAction {
+    pub event: TriggerEvent,
+    pub kind: TriggerActionKind,
+    pub target: Handle<Node>,
+    pub text: String,
+    pub duration: f32,
+    /// Seconds to wait after the event before running the action.
+    pub delay: f32,
+}
+
+/// A sensor volume that runs a list of actions when the player enters, stays in or leaves it.
+#[derive(Visit, Reflect, Debug, Clone)]
+pub struct Trigger {
+    pub actions: Vec<TriggerAction>,
+    /// Only reacts to the first time the player enters.
+    pub once: bool,
+    pub stay_interval: f32,
+
+    /// Times the player entered, with `once` only the first visit counts.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    entries: u32,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    stay_timer: f32,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    pending: Vec<(f32, usize)>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    timer: Option<(String, f32)>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    message: Option<(String, f32)>,
+}
+
+impl Default for Trigger {
+    fn default() -> Self {
+        Self {
+            actions: Vec::new(),
+            once: false,
+            stay_interval: 1.0,
+            entries: 0,
+            stay_timer: 0.0,
+            pending: Vec::new(),
+            timer: None,
+            message: None,
+        }
+    }
+}
+
+impl Trigger {
+    /// Text a `ShowMessage` action put on the HUD.
+    pub fn message(&self) -> Option<&str> {
+        self.message.as_ref().map(|(text, _)| text.as_str())
+    }
+
+    /// Label and remaining seconds of a running `StartTimer` countdown.
+    pub fn countdown(&self) -> Option<(&str, f32)> {
+        self.timer.as_ref().map(|(label, remaining)| (label.as_str(), *remaining))
+    }
+
+    fn fire(&mut self, event: TriggerEvent) {
+        for (index, action) in self.actions.iter().enumerate() {
+            if action.event == event {
+                self.pending.push((action.delay, index));
+            }
+        }
+    }
+
+    fn run(&mut self, index: usize, context: &mut ScriptContext) {
+        let action = self.actions[index].clone();
+        let graph = &mut context.scene.graph;
+        match action.kind {
+            TriggerActionKind::Enable | TriggerActionKind::Disable | TriggerActionKind::Toggle => {
+                if let Some(target) = graph.try_get_mut(action.target) {
+                    let enabled = match action.kind {
+                        TriggerActionKind::Enable => true,
+                        TriggerActionKind::Disable => false,
+                        _ => !target.is_enabled(),
+                    };
+                    target.set_enabled(enabled);
+                }
+            }
+            TriggerActionKind::PlaySound => {
+                if let Some(sound) = graph.try_get_mut(action.target) {
+                    sound.as_sound_mut().stop();
+                    sound.as_sound_mut().play();
+                }
+            }
+            TriggerActionKind::ShowMessage => self.message = Some((action.text, action.duration)),
+            TriggerActionKind::StartTimer => self.timer = Some((action.text, action.duration)),
+            TriggerActionKind::MovePlatform => {
+                context.message_sender.send_to_target(action.target, PlatformTriggerMessage { contact: false });
+            }
+        }
+    }
+}
+
+impl_component_provider!(Trigger);
+
+impl TypeUuidProvider for Trigger {
+    fn type_uuid() -> Uuid {
+        uuid!("5fd0a6e3-81c2-4b7d-9e14-3c6b28f07a95")
+    }
+}
+
+impl ScriptTrait for Trigger {
+    fn on_init(&mut self, _context: &mut ScriptContext) {}
+
+    fn on_start(&mut self, _context: &mut ScriptContext) {}
+
+    fn on_deinit(&mut self, _context: &mut ScriptDeinitContext) {}
+
+    fn on_os_event(&mut self, _event: &Event<()>, _context: &mut ScriptContext) {}
+
+    fn on_update(&mut self, context: &mut ScriptContext) {
+        for event in contact_events(context.plugins, context.handle) {
+            if event.phase == ContactPhase::Enter {
+                self.entries += 1;
+            }
+            if self.once && self.entries > 1 {
+                continue;
+            }
+            match event.phase {
+                ContactPhase::Enter => {
+                    self.stay_timer = self.stay_interval;
+                    self.fire(TriggerEvent::Enter);
+                }
+                ContactPhase::Stay => {
+                    self.stay_timer -= context.dt;
+                    if self.stay_timer <= 0.0 {
+                        self.stay_timer += self.stay_interval.max(context.dt);
+                        self.fire(TriggerEvent::Stay);
+                    }
+                }
+                ContactPhase::Exit => self.fire(TriggerEvent::Exit),
+            }
+        }
+
+        if let Some((_, remaining)) = self.timer.as_mut() {
+            *remaining -= context.dt;
+            if *remaining <= 0.0 {
+                self.timer = None;
+                self.fire(TriggerEvent::TimerElapsed);
+            }
+        }
+        if let Some((_, remaining)) = self.message.as_mut() {
+            *remaining -= context.dt;
+            if *remaining <= 0.0 {
+                self.message = None;
+            }
+        }
+
+        for (delay, _) in self.pending.iter_mut() {
+            *delay -= context.dt;
+        }
+        let mut due = Vec::new();
+        self.pending.retain(|(delay, index)| {
+            if *delay <= 0.0 {
+                due.push(*index);
+                false
+            } else {
+                true
+            }
+        });
+        for index in due {
+            self.run(index, context);
+        }
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+}
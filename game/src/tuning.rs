@@ -0,0 +1,118 @@
+use std::{
+    any::Any,
+    borrow::Cow,
+    path::{Path, PathBuf},
+};
+use fyrox::{
+    core::{uuid::{Uuid, uuid}, visitor::prelude::*, reflect::prelude::*, TypeUuidProvider, io},
+};
+use fyrox::core::log::Log;
+use fyrox::asset::{
+    event::ResourceEventBroadcaster,
+    loader::{BoxedLoaderFuture, ResourceLoader},
+    untyped::UntypedResource,
+    ResourceData,
+};
+use serde::{Deserialize, Serialize};
+
+/// Movement values read from `data/gameplay.tuning`, unset fields keep the prefab value. Loaded
+/// through the resource manager, so it reloads with the other data files while the game runs.
+#[derive(Visit, Reflect, Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct GameplayTuning {
+    pub accel_force: Option<f32>,
+    pub max_speed: Option<f32>,
+    pub jump_force: Option<f32>,
+    /// Downward speed while falling, as a multiple of `jump_force`.
+    pub fall_multiplier: Option<f32>,
+    /// Minimum vertical component of a contact normal that counts as ground.
+    pub ground_normal_threshold: Option<f32>,
+
+    #[serde(skip)]
+    #[visit(skip)]
+    #[reflect(hidden)]
+    path: PathBuf,
+}
+
+impl GameplayTuning {
+    pub const PATH: &'static str = "data/gameplay.tuning";
+
+    pub fn from_ron(text: &str) -> Result<Self, ron::error::SpannedError> {
+        ron::from_str(text)
+    }
+}
+
+impl TypeUuidProvider for GameplayTuning {
+    fn type_uuid() -> Uuid {
+        uuid!("c41e7a2f-6d3b-4f85-9b0e-58a2d7c1e934")
+    }
+}
+
+impl ResourceData for GameplayTuning {
+    fn path(&self) -> Cow<'_, Path> {
+        Cow::Borrowed(&self.path)
+    }
+
+    fn set_path(&mut self, path: PathBuf) {
+        self.path = path;
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn type_uuid(&self) -> Uuid {
+        <Self as TypeUuidProvider>::type_uuid()
+    }
+}
+
+/// Lets the resource manager load and hot-reload `*.tuning` files.
+pub struct TuningLoader;
+
+impl ResourceLoader for TuningLoader {
+    fn extensions(&self) -> &[&str] {
+        &["tuning"]
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn load(
+        &self,
+        resource: UntypedResource,
+        event_broadcaster: ResourceEventBroadcaster,
+        reload: bool,
+    ) -> BoxedLoaderFuture {
+        Box::pin(async move {
+            let path = resource.path().to_path_buf();
+            match io::load_file(&path).await {
+                Ok(bytes) => match GameplayTuning::from_ron(&String::from_utf8_lossy(&bytes)) {
+                    Ok(mut tuning) => {
+                        Log::info(format!("Loaded gameplay tuning from {}", path.display()));
+                        tuning.set_path(path);
+                        resource.commit_ok(tuning);
+                        event_broadcaster.broadcast_loaded_or_reloaded(resource, reload);
+                    }
+                    Err(err) => {
+                        Log::err(format!("Unable to parse gameplay tuning {}: {}", path.display(), err));
+                        resource.commit_error(path, err);
+                    }
+                },
+                Err(err) => resource.commit_error(path, err),
+            }
+        })
+    }
+}
@@ -0,0 +1,113 @@
+use std::collections::BTreeMap;
+use fyrox::core::pool::Handle;
+use battered_battery_bundles::achievements::{
+    AchievementCondition, AchievementDefinition, AchievementList, AchievementProgress, AchievementTracker, EventKind,
+};
+use battered_battery_bundles::difficulty::Difficulty;
+use battered_battery_bundles::events::{GameplayEvent, GameplayObserver};
+
+fn definition(id: &str, condition: AchievementCondition) -> AchievementDefinition {
+    AchievementDefinition {
+        id: id.to_string(),
+        name: id.to_string(),
+        description: String::new(),
+        condition,
+    }
+}
+
+fn tracker(definitions: Vec<AchievementDefinition>) -> AchievementTracker {
+    AchievementTracker::new(AchievementList { achievements: definitions }, BTreeMap::new())
+}
+
+fn delivery(count: usize) -> GameplayEvent {
+    GameplayEvent::Delivery { player: Handle::NONE, point: Handle::NONE, count, health: 0, max_health: 0 }
+}
+
+fn damage() -> GameplayEvent {
+    GameplayEvent::Damage { player: Handle::NONE, player_damage: 1, package_damage: 0 }
+}
+
+fn feed(tracker: &mut AchievementTracker, events: &[GameplayEvent]) {
+    for event in events {
+        tracker.on_event(event);
+    }
+}
+
+#[test]
+fn totals_unlock_once() {
+    let mut tracker = tracker(vec![definition("jumps", AchievementCondition::Total { event: EventKind::Jump, target: 3 })]);
+    let jump = GameplayEvent::Jump { player: Handle::NONE };
+    feed(&mut tracker, &[jump.clone(), damage(), jump.clone()]);
+    assert!(!tracker.is_unlocked("jumps"));
+    feed(&mut tracker, std::slice::from_ref(&jump));
+    assert!(tracker.is_unlocked("jumps"));
+    assert_eq!(tracker.take_unlocked(), ["jumps"]);
+    feed(&mut tracker, &[jump]);
+    assert!(tracker.take_unlocked().is_empty());
+}
+
+#[test]
+fn streaks_start_over_on_reset() {
+    let careful = AchievementCondition::Streak { event: EventKind::Delivery, reset_on: EventKind::Damage, target: 5 };
+    let mut tracker = tracker(vec![definition("careful", careful)]);
+    feed(&mut tracker, &[delivery(2), delivery(2), damage(), delivery(3)]);
+    assert!(!tracker.is_unlocked("careful"));
+    assert_eq!(tracker.progress()["careful"].progress, 3);
+    feed(&mut tracker, &[delivery(2)]);
+    assert!(tracker.is_unlocked("careful"));
+}
+
+#[test]
+fn level_completion_checks_level_and_difficulty() {
+    let weston_hard = AchievementCondition::CompleteLevel {
+        level: Some("westonscene1".to_string()),
+        difficulty: Some(Difficulty::Hard),
+    };
+    let mut tracker = tracker(vec![definition("weston_hard", weston_hard)]);
+    tracker.set_run("westonscene1", Difficulty::Normal);
+    feed(&mut tracker, &[GameplayEvent::LevelCompleted]);
+    tracker.set_run("scene", Difficulty::Hard);
+    feed(&mut tracker, &[GameplayEvent::LevelCompleted]);
+    assert!(!tracker.is_unlocked("weston_hard"));
+    tracker.set_run("westonscene1", Difficulty::Hard);
+    feed(&mut tracker, &[GameplayEvent::LevelCompleted]);
+    assert!(tracker.is_unlocked("weston_hard"));
+}
+
+#[test]
+fn saved_progress_carries_over() {
+    let mut progress = BTreeMap::new();
+    progress.insert("deliveries".to_string(), AchievementProgress { progress: 9, unlocked: false });
+    let list = AchievementList {
+        achievements: vec![definition("deliveries", AchievementCondition::Total { event: EventKind::Delivery, target: 10 })],
+    };
+    let mut tracker = AchievementTracker::new(list, progress);
+    feed(&mut tracker, &[delivery(1)]);
+    assert!(tracker.is_unlocked("deliveries"));
+}
+
+#[test]
+fn shipped_definitions_parse() {
+    let text = std::fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/../data/achievements.ron")).unwrap();
+    let list = AchievementList::from_ron(&text).unwrap();
+    assert!(!list.achievements.is_empty());
+}
+
+#[test]
+fn raising_difficulty_mid_run_does_not_count() {
+    let weston_hard = AchievementCondition::CompleteLevel {
+        level: Some("westonscene1".to_string()),
+        difficulty: Some(Difficulty::Hard),
+    };
+    let mut tracker = tracker(vec![definition("weston_hard", weston_hard)]);
+    tracker.set_run("westonscene1", Difficulty::Easy);
+    tracker.change_difficulty(Difficulty::Hard);
+    feed(&mut tracker, &[GameplayEvent::LevelCompleted]);
+    assert!(!tracker.is_unlocked("weston_hard"));
+
+    tracker.set_run("westonscene1", Difficulty::Hard);
+    tracker.change_difficulty(Difficulty::Easy);
+    tracker.change_difficulty(Difficulty::Hard);
+    feed(&mut tracker, &[GameplayEvent::LevelCompleted]);
+    assert!(!tracker.is_unlocked("weston_hard"));
+}
@@ -0,0 +1,431 @@
+//! Runs the game without a window: builds or loads a scene, creates the game plugin and steps
+//! physics, the plugin and scripts frame by frame.
+//!
+//! fyrox 0.31 only updates scenes, plugins and scripts from `Engine::update` once a window and
+//! renderer exist, and keeps its script processor private. The harness therefore runs the same
+//! cycle itself: scene update, plugin update, then script init, start, update and messages.
+#![allow(dead_code)]
+
+use std::any::TypeId;
+use std::mem::size_of;
+use std::path::Path;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Arc;
+use std::time::Duration;
+use fyrox::asset::manager::ResourceManager;
+use fyrox::core::algebra::{Vector2, Vector3};
+use fyrox::core::pool::Handle;
+use fyrox::core::uuid::{uuid, Uuid};
+use fyrox::core::{reflect::prelude::*, visitor::prelude::*, TypeUuidProvider};
+use fyrox::engine::{
+    Engine, EngineInitParams, GraphicsContextParams, PerformanceStatistics, ScriptMessageDispatcher,
+    SerializationContext,
+};
+use fyrox::event_loop::ControlFlow;
+use fyrox::fxhash::{FxHashMap, FxHashSet};
+use fyrox::impl_component_provider;
+use fyrox::plugin::{Plugin, PluginConstructor, PluginContext};
+use fyrox::scene::base::BaseBuilder;
+use fyrox::scene::collider::{ColliderBuilder, ColliderShape};
+use fyrox::scene::graph::Graph;
+use fyrox::scene::loader::AsyncSceneLoader;
+use fyrox::scene::node::Node;
+use fyrox::scene::rigidbody::{RigidBodyBuilder, RigidBodyType};
+use fyrox::scene::transform::TransformBuilder;
+use fyrox::scene::Scene;
+use fyrox::script::{
+    RoutingStrategy, Script, ScriptContext, ScriptMessage, ScriptMessageContext, ScriptMessageKind,
+    ScriptMessageSender, ScriptTrait,
+};
+use fyrox::window::WindowAttributes;
+use battered_battery_bundles::GameConstructor;
+use battered_battery_bundles::input::{InputAction, InputRecording, InputState};
+use battered_battery_bundles::player_controller::PlayerController;
+use battered_battery_bundles::tuning::GameplayTuning;
+
+pub const DT: f32 = 1.0 / 60.0;
+
+pub struct Harness {
+    pub engine: Engine,
+    pub scene: Handle<Scene>,
+    pub frame: u64,
+    plugins: Vec<Box<dyn Plugin>>,
+    scripts: ScriptRunner,
+    statistics: PerformanceStatistics,
+}
+
+fn engine() -> Engine {
+    let mut engine = Engine::new(EngineInitParams {
+        graphics_context_params: GraphicsContextParams {
+            window_attributes: WindowAttributes::default(),
+            vsync: false,
+        },
+        resource_manager: ResourceManager::new(),
+        serialization_context: Arc::new(SerializationContext::new()),
+    })
+    .unwrap();
+    GameConstructor::register_resources(&engine.resource_manager);
+    engine.add_plugin_constructor(GameConstructor);
+    engine
+}
+
+impl Harness {
+    /// Runs a scene built in code.
+    pub fn new(scene: Scene) -> Self {
+        Self::start(engine(), scene)
+    }
+
+    /// Runs a scene file, paths are relative to the workspace root like in the game. The path is
+    /// resolved against this crate rather than changing the working directory, which tests share.
+    pub fn load(path: &str) -> Self {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("..").join(path);
+        let engine = engine();
+        let loader = AsyncSceneLoader::begin_loading(
+            path,
+            engine.serialization_context.clone(),
+            engine.resource_manager.clone(),
+        );
+        let scene = loop {
+            match loader.fetch_result() {
+                Some(result) => break result.unwrap(),
+                None => std::thread::sleep(Duration::from_millis(1)),
+            }
+        };
+        Self::start(engine, scene)
+    }
+
+    fn start(mut engine: Engine, scene: Scene) -> Self {
+        let scene = engine.scenes.add(scene);
+        while !engine.resource_manager.state().get_wait_context().is_all_loaded() {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        let statistics = PerformanceStatistics::default();
+        let plugin = GameConstructor.create_instance(scene, PluginContext {
+            scenes: &mut engine.scenes,
+            resource_manager: &engine.resource_manager,
+            user_interface: &mut engine.user_interface,
+            graphics_context: &mut engine.graphics_context,
+            dt: DT,
+            lag: &mut 0.0,
+            serialization_context: &engine.serialization_context,
+            performance_statistics: &statistics,
+            elapsed_time: 0.0,
+        });
+        Self {
+            engine,
+            scene,
+            frame: 0,
+            plugins: vec![plugin],
+            scripts: ScriptRunner::new(),
+            statistics,
+        }
+    }
+
+    pub fn step(&mut self, frames: u32) {
+        for _ in 0..frames {
+            let elapsed_time = self.frame as f32 * DT;
+            self.engine.resource_manager.state().update(DT);
+            self.engine.scenes[self.scene].update(Vector2::new(1.0, 1.0), DT, Default::default());
+
+            let mut context = PluginContext {
+                scenes: &mut self.engine.scenes,
+                resource_manager: &self.engine.resource_manager,
+                user_interface: &mut self.engine.user_interface,
+                graphics_context: &mut self.engine.graphics_context,
+                dt: DT,
+                lag: &mut 0.0,
+                serialization_context: &self.engine.serialization_context,
+                performance_statistics: &self.statistics,
+                elapsed_time,
+            };
+            for plugin in self.plugins.iter_mut() {
+                plugin.update(&mut context, &mut ControlFlow::Poll);
+            }
+
+            self.scripts.update(
+                &mut self.engine.scenes[self.scene],
+                &mut self.plugins,
+                &self.engine.resource_manager,
+                elapsed_time,
+            );
+            self.frame += 1;
+        }
+    }
+
+    /// Steps until the condition holds, returns false if it didn't within `max_frames`.
+    pub fn step_until(&mut self, max_frames: u32, mut condition: impl FnMut(&Harness) -> bool) -> bool {
+        for _ in 0..max_frames {
+            if condition(self) {
+                return true;
+            }
+            self.step(1);
+        }
+        condition(self)
+    }
+
+    pub fn input(&mut self) -> &mut InputState {
+        InputState::find_mut(&mut self.plugins).expect("game plugin is not loaded")
+    }
+
+    /// Queues an action for the next frame, like a key press.
+    pub fn press(&mut self, action: InputAction) {
+        self.input().push(action);
+    }
+
+    /// Plays a recorded run back from the current frame on.
+    pub fn replay(&mut self, recording: InputRecording) {
+        let input = self.input();
+        input.replay(recording);
+        input.restart("");
+    }
+
+    pub fn graph(&self) -> &Graph {
+        &self.engine.scenes[self.scene].graph
+    }
+
+    pub fn graph_mut(&mut self) -> &mut Graph {
+        &mut self.engine.scenes[self.scene].graph
+    }
+
+    pub fn plugins(&self) -> &[Box<dyn Plugin>] {
+        &self.plugins
+    }
+
+    pub fn plugins_mut(&mut self) -> &mut [Box<dyn Plugin>] {
+        &mut self.plugins
+    }
+
+    pub fn player_handle(&self) -> Handle<Node> {
+        self.graph()
+            .find_from_root(&mut |n| n.has_script::<PlayerController>())
+            .map(|(handle, _)| handle)
+            .expect("scene has no player")
+    }
+
+    pub fn player(&self) -> &PlayerController {
+        self.graph()[self.player_handle()].try_get_script::<PlayerController>().unwrap()
+    }
+
+    pub fn player_mut(&mut self) -> &mut PlayerController {
+        let handle = self.player_handle();
+        self.graph_mut()[handle].try_get_script_mut::<PlayerController>().unwrap()
+    }
+}
+
+/// Same fields as fyrox's `ScriptMessageDispatcher`, which can only be built inside the engine.
+struct Dispatcher {
+    type_groups: FxHashMap<TypeId, FxHashSet<Handle<Node>>>,
+    message_receiver: Receiver<ScriptMessage>,
+}
+
+const _: () = assert!(size_of::<Dispatcher>() == size_of::<ScriptMessageDispatcher>());
+const _: () = assert!(size_of::<Sender<ScriptMessage>>() == size_of::<ScriptMessageSender>());
+
+/// Stands in for a script while it runs, like the engine taking it out of its node.
+#[derive(Visit, Reflect, Default, Debug, Clone)]
+struct Detached;
+
+impl_component_provider!(Detached);
+
+impl TypeUuidProvider for Detached {
+    fn type_uuid() -> Uuid {
+        uuid!("0f6d2c7a-3e91-4b58-a4d0-7c2e95b13f86")
+    }
+}
+
+impl ScriptTrait for Detached {
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+}
+
+/// Initializes, starts and updates scripts and delivers their messages in the engine's order.
+struct ScriptRunner {
+    sender: ScriptMessageSender,
+    dispatcher: Dispatcher,
+    started: FxHashSet<Handle<Node>>,
+}
+
+impl ScriptRunner {
+    fn new() -> Self {
+        let (sender, message_receiver) = channel();
+        Self {
+            // SAFETY: `ScriptMessageSender` is a single `Sender<ScriptMessage>` field, checked above.
+            sender: unsafe { std::mem::transmute::<Sender<ScriptMessage>, ScriptMessageSender>(sender) },
+            dispatcher: Dispatcher { type_groups: Default::default(), message_receiver },
+            started: Default::default(),
+        }
+    }
+
+    fn dispatcher(&mut self) -> &mut ScriptMessageDispatcher {
+        // SAFETY: `Dispatcher` declares the same fields in the same order, checked above.
+        unsafe { &mut *(&mut self.dispatcher as *mut Dispatcher as *mut ScriptMessageDispatcher) }
+    }
+
+    fn update(&mut self, scene: &mut Scene, plugins: &mut [Box<dyn Plugin>], resource_manager: &ResourceManager, elapsed_time: f32) {
+        let mut update_queue = self.started.iter().copied().collect::<Vec<_>>();
+        update_queue.sort_by_key(|h| h.index());
+        self.started.retain(|h| scene.graph.try_get(*h).is_some_and(|n| n.script().is_some()));
+
+        for _ in 0..64 {
+            // Scripts spawned by `on_start` or `on_message` are started before the update, like in
+            // the engine, so they don't lag a frame behind.
+            loop {
+                let new = scene.graph.pair_iter()
+                    .filter(|(h, n)| n.script().is_some() && !self.started.contains(h))
+                    .map(|(h, _)| h)
+                    .collect::<Vec<_>>();
+                if new.is_empty() {
+                    break;
+                }
+                for &handle in new.iter() {
+                    self.run(handle, scene, plugins, resource_manager, elapsed_time, |s, ctx| s.on_init(ctx));
+                }
+                for &handle in new.iter() {
+                    self.started.insert(handle);
+                    self.run(handle, scene, plugins, resource_manager, elapsed_time, |s, ctx| s.on_start(ctx));
+                    update_queue.push(handle);
+                }
+            }
+            if update_queue.is_empty() {
+                break;
+            }
+            for handle in std::mem::take(&mut update_queue) {
+                self.run(handle, scene, plugins, resource_manager, elapsed_time, |s, ctx| s.on_update(ctx));
+            }
+            self.dispatch(scene, plugins, resource_manager, elapsed_time);
+        }
+    }
+
+    fn run(
+        &mut self,
+        handle: Handle<Node>,
+        scene: &mut Scene,
+        plugins: &mut [Box<dyn Plugin>],
+        resource_manager: &ResourceManager,
+        elapsed_time: f32,
+        func: impl FnOnce(&mut Script, &mut ScriptContext),
+    ) {
+        let mut script = match take_script(scene, handle) {
+            Some(script) => script,
+            None => return,
+        };
+        let sender = self.sender.clone();
+        let mut context = ScriptContext {
+            dt: DT,
+            elapsed_time,
+            plugins,
+            handle,
+            scene,
+            resource_manager,
+            message_sender: &sender,
+            message_dispatcher: self.dispatcher(),
+        };
+        func(&mut script, &mut context);
+        put_script(scene, handle, script);
+    }
+
+    fn dispatch(&mut self, scene: &mut Scene, plugins: &mut [Box<dyn Plugin>], resource_manager: &ResourceManager, elapsed_time: f32) {
+        while let Ok(message) = self.dispatcher.message_receiver.try_recv() {
+            let mut payload = message.payload;
+            let receivers = match self.dispatcher.type_groups.get(&(*payload).as_any_ref().type_id()) {
+                Some(receivers) => receivers.clone(),
+                None => continue,
+            };
+            let targets = match message.kind {
+                ScriptMessageKind::Targeted(target) => vec![target],
+                ScriptMessageKind::Hierarchical { root, routing: RoutingStrategy::Up } => {
+                    let mut targets = Vec::new();
+                    let mut node = root;
+                    while let Some(node_ref) = scene.graph.try_get(node) {
+                        targets.push(node);
+                        node = node_ref.parent();
+                    }
+                    targets
+                }
+                ScriptMessageKind::Hierarchical { root, routing: RoutingStrategy::Down } => {
+                    scene.graph.traverse_handle_iter(root).collect()
+                }
+                ScriptMessageKind::Global => receivers.iter().copied().collect(),
+            };
+            for target in targets.into_iter().filter(|t| receivers.contains(t)) {
+                let mut script = match take_script(scene, target) {
+                    Some(script) => script,
+                    None => continue,
+                };
+                let mut context = ScriptMessageContext {
+                    dt: DT,
+                    elapsed_time,
+                    plugins,
+                    handle: target,
+                    scene,
+                    resource_manager,
+                    message_sender: &self.sender,
+                };
+                script.on_message(&mut *payload, &mut context);
+                put_script(scene, target, script);
+            }
+        }
+    }
+}
+
+fn take_script(scene: &mut Scene, handle: Handle<Node>) -> Option<Script> {
+    let node = scene.graph.try_get_mut(handle)?;
+    if !node.is_globally_enabled() || node.try_get_script::<Detached>().is_some() {
+        return None;
+    }
+    node.script_mut().map(|script| std::mem::replace(script, Script::new(Detached)))
+}
+
+fn put_script(scene: &mut Scene, handle: Handle<Node>, script: Script) {
+    if let Some(slot) = scene.graph.try_get_mut(handle).and_then(|n| n.script_mut()) {
+        *slot = script;
+    }
+}
+
+fn at(position: Vector3<f32>) -> BaseBuilder {
+    BaseBuilder::new().with_local_transform(TransformBuilder::new().with_local_position(position).build())
+}
+
+/// A flat static floor with its top at y = 0.
+pub fn ground(scene: &mut Scene, half_width: f32) -> Handle<Node> {
+    let collider = ColliderBuilder::new(BaseBuilder::new())
+        .with_shape(ColliderShape::cuboid(half_width, 0.5, 1.0))
+        .build(&mut scene.graph);
+    RigidBodyBuilder::new(at(Vector3::new(0.0, -0.5, 0.0)).with_children(&[collider]))
+        .with_body_type(RigidBodyType::Static)
+        .build(&mut scene.graph)
+}
+
+/// A courier standing on the ground at `x`, with one package slot and some speed.
+pub fn player(scene: &mut Scene, x: f32) -> Handle<Node> {
+    let collider = ColliderBuilder::new(BaseBuilder::new())
+        .with_shape(ColliderShape::ball(0.25))
+        .build(&mut scene.graph);
+    let mut controller = PlayerController::default();
+    controller.set_collider(collider);
+    controller.player_health = 100;
+    controller.package_health = 100;
+    let mut tuning = GameplayTuning::default();
+    tuning.accel_force = Some(10.0);
+    tuning.max_speed = Some(2.0);
+    tuning.jump_force = Some(5.0);
+    controller.apply_tuning(&tuning);
+    RigidBodyBuilder::new(at(Vector3::new(x, 0.25, 0.0))
+        .with_children(&[collider])
+        .with_script(Script::new(controller)))
+        .with_locked_rotations(true)
+        .build(&mut scene.graph)
+}
+
+/// A static collider with a script on it, a sensor zone or a solid block.
+pub fn block(scene: &mut Scene, position: Vector3<f32>, half_extents: Vector3<f32>, sensor: bool, script: Script) -> Handle<Node> {
+    let collider = ColliderBuilder::new(BaseBuilder::new().with_script(script))
+        .with_shape(ColliderShape::cuboid(half_extents.x, half_extents.y, half_extents.z))
+        .with_sensor(sensor)
+        .build(&mut scene.graph);
+    RigidBodyBuilder::new(at(position).with_children(&[collider]))
+        .with_body_type(RigidBodyType::Static)
+        .build(&mut scene.graph);
+    collider
+}
@@ -0,0 +1,41 @@
+use battered_battery_bundles::daily::{civil_from_days, seed_from_date, ChallengeModifier, DailyChallenge, DAILY_LEVELS};
+use battered_battery_bundles::difficulty::DifficultyModifiers;
+
+#[test]
+fn same_date_gives_same_challenge() {
+    let first = DailyChallenge::for_date("2024-03-01");
+    let second = DailyChallenge::for_date("2024-03-01");
+    assert_eq!(first, second);
+    assert!(DAILY_LEVELS.contains(&first.level.as_str()));
+    assert!(!first.modifiers.is_empty());
+}
+
+#[test]
+fn seeds_differ_between_days() {
+    assert_ne!(seed_from_date("2024-03-01"), seed_from_date("2024-03-02"));
+    // The seed must not change between builds, players compare runs across versions.
+    assert_eq!(seed_from_date(""), 0xcbf2_9ce4_8422_2325);
+}
+
+#[test]
+fn modifiers_stack_on_difficulty() {
+    let challenge = DailyChallenge::for_date("2024-03-01");
+    let mut modifiers = DifficultyModifiers::default();
+    challenge.apply(&mut modifiers);
+    assert_ne!(modifiers, DifficultyModifiers::default());
+}
+
+#[test]
+fn days_convert_to_calendar_dates() {
+    assert_eq!(civil_from_days(0), (1970, 1, 1));
+    assert_eq!(civil_from_days(11_016), (2000, 2, 29));
+    assert_eq!(civil_from_days(19_783), (2024, 3, 1));
+}
+
+#[test]
+fn challenge_is_pinned_across_builds() {
+    // If this changes, every player's daily run changed with it.
+    let challenge = DailyChallenge::for_date("2024-03-01");
+    assert_eq!(challenge.level, "data/westonscene1.rgs");
+    assert_eq!(challenge.modifiers, [ChallengeModifier::LowBattery]);
+}
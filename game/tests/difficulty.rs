@@ -0,0 +1,36 @@
+mod common;
+
+use fyrox::scene::Scene;
+use battered_battery_bundles::difficulty::{CustomSetting, DifficultyModifiers};
+use common::{ground, player, Harness};
+
+#[test]
+fn custom_values_stay_in_range() {
+    let mut custom = DifficultyModifiers::default();
+    for _ in 0..20 {
+        CustomSetting::Damage.adjust(&mut custom, CustomSetting::STEP);
+        CustomSetting::StartingBattery.adjust(&mut custom, CustomSetting::STEP);
+        CustomSetting::PlayerHealth.adjust(&mut custom, -CustomSetting::STEP);
+    }
+    assert_eq!(custom.damage, 3.0);
+    assert_eq!(custom.starting_battery, 1.0);
+    assert_eq!(custom.player_health, 0.25);
+    assert_eq!(CustomSetting::Damage.get(&custom), 3.0);
+}
+
+#[test]
+fn changing_difficulty_mid_run_rescales_health_right_away() {
+    let mut scene = Scene::new();
+    ground(&mut scene, 20.0);
+    player(&mut scene, 0.0);
+    let mut harness = Harness::new(scene);
+    harness.step(1);
+    assert_eq!(harness.player().actual_player_health, 100);
+
+    let fragile = DifficultyModifiers { player_health: 0.5, ..Default::default() };
+    harness.player_mut().apply_difficulty(fragile);
+    assert_eq!(harness.player().actual_player_health, 50);
+
+    harness.player_mut().apply_difficulty(DifficultyModifiers::default());
+    assert_eq!(harness.player().actual_player_health, 100);
+}
@@ -0,0 +1,67 @@
+mod common;
+
+use std::path::Path;
+use battered_battery_bundles::endless::{EndlessGenerator, EndlessLayout, EndlessMode};
+use common::Harness;
+
+fn layout() -> EndlessLayout {
+    EndlessLayout::from_ron(&std::fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/../data/endless.ron")).unwrap())
+        .unwrap()
+}
+
+fn run(seed: u64, chunks: usize) -> Vec<(usize, usize)> {
+    let mut generator = EndlessGenerator::new(layout(), seed);
+    (0..chunks)
+        .map(|_| generator.next_chunk().unwrap())
+        .map(|c| (c.template, c.pieces.len()))
+        .collect()
+}
+
+#[test]
+fn the_same_seed_builds_the_same_track() {
+    assert_eq!(run(7, 200), run(7, 200));
+    assert_ne!(run(7, 200), run(8, 200));
+}
+
+#[test]
+fn chunks_follow_each_other_without_gaps() {
+    let mut generator = EndlessGenerator::new(layout(), 1);
+    let mut end = 0.0;
+    for _ in 0..50 {
+        let chunk = generator.next_chunk().unwrap();
+        assert_eq!(chunk.start, end);
+        end = chunk.start + chunk.length;
+    }
+}
+
+#[test]
+fn harder_chunks_only_appear_further_in() {
+    let layout = layout();
+    let ziggurat = layout.chunks.iter().position(|c| c.name == "Ziggurat").unwrap();
+    let mut generator = EndlessGenerator::new(layout.clone(), 3);
+    while generator.distance() < 3000.0 {
+        let chunk = generator.next_chunk().unwrap();
+        if chunk.template == ziggurat {
+            assert!(layout.difficulty_at(chunk.start) >= layout.chunks[ziggurat].min_difficulty);
+        }
+    }
+}
+
+#[test]
+fn every_chunk_prefab_is_shipped() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("..");
+    for piece in layout().chunks.iter().flat_map(|c| c.pieces.iter()) {
+        assert!(root.join(&piece.prefab).exists(), "{} is missing", piece.prefab);
+    }
+}
+
+#[test]
+fn the_endless_scene_builds_its_track_and_player() {
+    let harness = Harness::load("data/endless.rgs");
+    let endless = harness.graph()
+        .linear_iter()
+        .find_map(|n| n.try_get_script::<EndlessMode>())
+        .expect("endless scene has no track");
+    assert_eq!(endless.layout, "data/endless.ron");
+    assert_eq!(endless.player_prefab, "data/Prefabs/Player.rgs");
+}
@@ -0,0 +1,121 @@
+mod common;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use fyrox::core::{uuid::{Uuid, uuid}, visitor::prelude::*, reflect::prelude::*, TypeUuidProvider};
+use fyrox::core::pool::Handle;
+use fyrox::impl_component_provider;
+use fyrox::scene::base::BaseBuilder;
+use fyrox::scene::node::Node;
+use fyrox::scene::pivot::PivotBuilder;
+use fyrox::scene::Scene;
+use fyrox::script::{Script, ScriptContext, ScriptMessageContext, ScriptMessagePayload, ScriptTrait};
+use battered_battery_bundles::events::{subscribe, EventBus, GameplayEvent, GameplayObserver};
+use battered_battery_bundles::input::InputAction;
+use battered_battery_bundles::pursuer::Pursuer;
+use common::{ground, player, Harness};
+
+/// Counts the gameplay events it gets as script messages.
+#[derive(Visit, Reflect, Default, Debug, Clone)]
+struct Listener {
+    #[visit(skip)]
+    #[reflect(hidden)]
+    received: Vec<GameplayEvent>,
+}
+
+impl_component_provider!(Listener);
+
+impl TypeUuidProvider for Listener {
+    fn type_uuid() -> Uuid {
+        uuid!("b5e0c2d4-1f7a-4e39-8a6b-3d9c0f2e7a18")
+    }
+}
+
+impl ScriptTrait for Listener {
+    fn on_start(&mut self, context: &mut ScriptContext) {
+        subscribe(context);
+    }
+
+    fn on_message(&mut self, message: &mut dyn ScriptMessagePayload, _ctx: &mut ScriptMessageContext) {
+        if let Some(event) = message.downcast_ref::<GameplayEvent>() {
+            self.received.push(event.clone());
+        }
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+}
+
+struct Counter(Rc<RefCell<Vec<GameplayEvent>>>);
+
+impl GameplayObserver for Counter {
+    fn on_event(&mut self, event: &GameplayEvent) {
+        self.0.borrow_mut().push(event.clone());
+    }
+}
+
+fn scene() -> (Scene, Handle<Node>) {
+    let mut scene = Scene::new();
+    ground(&mut scene, 20.0);
+    player(&mut scene, 0.0);
+    let listener = PivotBuilder::new(BaseBuilder::new().with_script(Script::new(Listener::default())))
+        .build(&mut scene.graph);
+    (scene, listener)
+}
+
+fn jumps(events: &[GameplayEvent]) -> usize {
+    events.iter().filter(|e| matches!(e, GameplayEvent::Jump { .. })).count()
+}
+
+#[test]
+fn subscribed_scripts_and_observers_get_each_event_once() {
+    let (scene, listener) = scene();
+    let mut harness = Harness::new(scene);
+    let observed = Rc::new(RefCell::new(Vec::new()));
+    EventBus::find_mut(harness.plugins_mut()).unwrap().observe(Box::new(Counter(observed.clone())));
+    harness.step(30);
+
+    harness.press(InputAction::JumpPressed);
+    assert!(harness.step_until(10, |h| {
+        jumps(&h.graph()[listener].try_get_script::<Listener>().unwrap().received) == 1
+    }));
+    harness.step(10);
+    let received = &harness.graph()[listener].try_get_script::<Listener>().unwrap().received;
+    assert_eq!(jumps(received), 1);
+    assert_eq!(jumps(&observed.borrow()), 1);
+    assert!(received.contains(&GameplayEvent::Jump { player: harness.player_handle() }));
+}
+
+#[test]
+fn recording_keeps_events_with_their_frame() {
+    let (scene, _) = scene();
+    let mut harness = Harness::new(scene);
+    harness.step(30);
+    EventBus::find_mut(harness.plugins_mut()).unwrap().start_recording();
+
+    harness.press(InputAction::JumpPressed);
+    harness.step(10);
+    let recorded = EventBus::find_mut(harness.plugins_mut()).unwrap().stop_recording();
+    let jump = recorded.iter().find(|r| matches!(r.event, GameplayEvent::Jump { .. })).unwrap();
+    assert!(jump.frame > 30);
+    assert_eq!(recorded.iter().filter(|r| r.event == jump.event).count(), 1);
+    assert!(EventBus::find(harness.plugins()).unwrap().recorded().is_empty());
+}
+
+#[test]
+fn harmless_hits_are_not_damage() {
+    let (mut scene, _) = scene();
+    let mut pursuer = Pursuer::default();
+    pursuer.start_delay = 0.0;
+    pursuer.player_damage = 0;
+    pursuer.package_damage = 0;
+    PivotBuilder::new(BaseBuilder::new().with_script(Script::new(pursuer))).build(&mut scene.graph);
+    let mut harness = Harness::new(scene);
+    EventBus::find_mut(harness.plugins_mut()).unwrap().start_recording();
+
+    // The pursuer appears on the player's path and catches them right away.
+    harness.step(30);
+    let recorded = EventBus::find_mut(harness.plugins_mut()).unwrap().stop_recording();
+    assert!(!recorded.iter().any(|r| matches!(r.event, GameplayEvent::Damage { .. })));
+}
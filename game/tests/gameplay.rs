@@ -0,0 +1,214 @@
+mod common;
+
+use fyrox::core::algebra::Vector3;
+use fyrox::scene::base::BaseBuilder;
+use fyrox::scene::collider::{ColliderBuilder, ColliderShape};
+use fyrox::scene::pivot::PivotBuilder;
+use fyrox::scene::rigidbody::RigidBodyBuilder;
+use fyrox::scene::transform::TransformBuilder;
+use fyrox::scene::Scene;
+use fyrox::script::Script;
+use battered_battery_bundles::collectible::{Collectible, CollectiblePersistence};
+use battered_battery_bundles::jump_pad::JumpPad;
+use battered_battery_bundles::one_way_platform::OneWayPlatform;
+use battered_battery_bundles::package_stack::Package;
+use battered_battery_bundles::player_controller::MoveDirection;
+use battered_battery_bundles::pursuer::Pursuer;
+use battered_battery_bundles::reverse_direction::ReverseDirection;
+use battered_battery_bundles::terrain_effect::TerrainEffect;
+use battered_battery_bundles::traffic::TrafficVehicle;
+use battered_battery_bundles::trigger::{Trigger, TriggerAction, TriggerActionKind, TriggerEvent};
+use common::{block, ground, player, Harness};
+
+#[test]
+fn oil_spill_damages_the_package_once_on_entry() {
+    let mut scene = Scene::new();
+    ground(&mut scene, 20.0);
+    player(&mut scene, 0.0);
+    let mut oil_spill = TerrainEffect::default();
+    oil_spill.package_damage = 15;
+    block(&mut scene, Vector3::new(-2.0, 0.25, 0.0), Vector3::new(0.5, 0.5, 1.0), true, Script::new(oil_spill));
+
+    let mut harness = Harness::new(scene);
+    harness.step(1);
+    harness.player_mut().cargo.push(Package::new(100, ""));
+
+    assert!(harness.step_until(300, |h| h.player().cargo.packages()[0].health < 100));
+    harness.step(30);
+    assert_eq!(harness.player().cargo.packages()[0].health, 85);
+}
+
+#[test]
+fn damage_over_time_adds_up_while_inside() {
+    let mut scene = Scene::new();
+    ground(&mut scene, 20.0);
+    player(&mut scene, 0.0);
+    let mut sprinkler = TerrainEffect::default();
+    sprinkler.player_damage_per_second = 10.0;
+    block(&mut scene, Vector3::new(0.0, 0.25, 0.0), Vector3::new(10.0, 0.5, 1.0), true, Script::new(sprinkler));
+
+    let mut harness = Harness::new(scene);
+    harness.step(61);
+    let health = harness.player().actual_player_health;
+    assert!((88..=92).contains(&health), "health {}", health);
+}
+
+#[test]
+fn touching_reverse_direction_flips_once() {
+    let mut scene = Scene::new();
+    ground(&mut scene, 20.0);
+    player(&mut scene, 0.0);
+    block(&mut scene, Vector3::new(-3.0, 0.5, 0.0), Vector3::new(0.25, 1.0, 1.0), false, Script::new(ReverseDirection::default()));
+
+    let mut harness = Harness::new(scene);
+    assert_eq!(harness.player().direction(), &MoveDirection::Right);
+
+    assert!(harness.step_until(600, |h| h.player().direction() == &MoveDirection::Left));
+    harness.step(120);
+    assert_eq!(harness.player().direction(), &MoveDirection::Left);
+}
+
+#[test]
+fn jump_pad_launches_the_player_above_it() {
+    let mut scene = Scene::new();
+    ground(&mut scene, 20.0);
+    player(&mut scene, 0.0);
+    let mut pad = JumpPad::default();
+    pad.launch_impulse = Vector3::new(0.0, 8.0, 0.0);
+    pad.reset_vertical_velocity = true;
+    block(&mut scene, Vector3::new(-2.0, 0.25, 0.0), Vector3::new(0.5, 0.25, 1.0), true, Script::new(pad));
+
+    let mut harness = Harness::new(scene);
+    // The pad's top is at 0.5, a launch that isn't cancelled carries the player well above it.
+    assert!(harness.step_until(300, |h| h.graph()[h.player_handle()].global_position().y > 1.5));
+}
+
+#[test]
+fn traffic_knocks_the_player_back_into_the_air() {
+    let mut scene = Scene::new();
+    ground(&mut scene, 20.0);
+    player(&mut scene, 0.0);
+    let collider = ColliderBuilder::new(BaseBuilder::new())
+        .with_shape(ColliderShape::cuboid(1.0, 0.5, 1.0))
+        .build(&mut scene.graph);
+    let mut vehicle = TrafficVehicle::default();
+    vehicle.set_collider(collider);
+    vehicle.player_damage = 10;
+    vehicle.knockback = 4.0;
+    vehicle.knockback_lift = 8.0;
+    vehicle.begin(vec![Vector3::new(-8.0, 0.5, 0.0), Vector3::new(12.0, 0.5, 0.0)], 3.0);
+    RigidBodyBuilder::new(BaseBuilder::new()
+        .with_local_transform(TransformBuilder::new().with_local_position(Vector3::new(-8.0, 0.5, 0.0)).build())
+        .with_children(&[collider])
+        .with_script(Script::new(vehicle)))
+        .build(&mut scene.graph);
+
+    let mut harness = Harness::new(scene);
+    // The vehicle is a meter tall, the lift has to survive the next frames to clear it.
+    assert!(harness.step_until(300, |h| h.graph()[h.player_handle()].global_position().y > 1.5));
+    assert_eq!(harness.player().actual_player_health, 90);
+}
+
+#[test]
+fn trigger_runs_enter_and_exit_actions_from_contact_events() {
+    let mut scene = Scene::new();
+    ground(&mut scene, 20.0);
+    player(&mut scene, 0.0);
+    let mut trigger = Trigger::default();
+    for (event, text) in [(TriggerEvent::Enter, "in"), (TriggerEvent::Exit, "out")] {
+        trigger.actions.push(TriggerAction {
+            event,
+            kind: TriggerActionKind::ShowMessage,
+            text: text.to_string(),
+            duration: 10.0,
+            ..Default::default()
+        });
+    }
+    let zone = block(&mut scene, Vector3::new(-2.0, 0.5, 0.0), Vector3::new(0.5, 1.0, 1.0), true, Script::new(trigger));
+
+    let mut harness = Harness::new(scene);
+    let message = |h: &Harness| h.graph()[zone].try_get_script::<Trigger>().unwrap().message().map(str::to_string);
+    assert!(harness.step_until(300, |h| message(h).as_deref() == Some("in")));
+    assert!(harness.step_until(300, |h| message(h).as_deref() == Some("out")));
+}
+
+#[test]
+fn one_way_platform_keeps_holding_other_bodies_while_the_player_is_below() {
+    let mut scene = Scene::new();
+    ground(&mut scene, 20.0);
+    player(&mut scene, 0.0);
+    let mut platform = OneWayPlatform::default();
+    platform.surface_offset = 0.1;
+    block(&mut scene, Vector3::new(0.0, 2.0, 0.0), Vector3::new(3.0, 0.1, 1.0), false, Script::new(platform));
+    let crate_collider = ColliderBuilder::new(BaseBuilder::new())
+        .with_shape(ColliderShape::cuboid(0.2, 0.2, 0.2))
+        .build(&mut scene.graph);
+    let debris = RigidBodyBuilder::new(BaseBuilder::new()
+        .with_local_transform(TransformBuilder::new().with_local_position(Vector3::new(1.0, 2.5, 0.0)).build())
+        .with_children(&[crate_collider]))
+        .build(&mut scene.graph);
+
+    let mut harness = Harness::new(scene);
+    harness.step(120);
+    // The player is under the platform the whole time, so it lets them through but not the crate.
+    assert!(harness.graph()[debris].global_position().y > 2.0);
+}
+
+#[test]
+fn pursuer_stays_put_while_recovering_from_a_catch() {
+    let mut scene = Scene::new();
+    ground(&mut scene, 40.0);
+    player(&mut scene, 0.0);
+    let mut pursuer = Pursuer::default();
+    pursuer.start_delay = 0.0;
+    pursuer.delay = 0.5;
+    pursuer.max_delay = 1.0;
+    pursuer.player_damage = 10;
+    pursuer.package_damage = 0;
+    pursuer.recover_time = 2.0;
+    let pursuer = PivotBuilder::new(BaseBuilder::new().with_script(Script::new(pursuer))).build(&mut scene.graph);
+
+    let mut harness = Harness::new(scene);
+    // Health is only set up once the player's script starts.
+    harness.step(1);
+    assert!(harness.step_until(300, |h| h.player().actual_player_health < 100));
+    harness.step(1);
+    let caught_at = harness.graph()[pursuer].global_position();
+    harness.step(90);
+    assert_eq!(harness.graph()[pursuer].global_position(), caught_at);
+}
+
+#[test]
+fn respawned_collectible_waits_for_the_player_to_step_off() {
+    let mut scene = Scene::new();
+    ground(&mut scene, 20.0);
+    player(&mut scene, 0.0);
+    let mut collectible = Collectible::default();
+    collectible.persistence = CollectiblePersistence::Respawn;
+    collectible.respawn_time = 0.5;
+    let collectible = block(&mut scene, Vector3::new(0.0, 0.25, 0.0), Vector3::new(10.0, 1.0, 1.0), true, Script::new(collectible));
+
+    let mut harness = Harness::new(scene);
+    let collected = |h: &Harness| h.graph()[collectible].try_get_script::<Collectible>().unwrap().is_collected();
+    assert!(harness.step_until(60, |h| collected(h)));
+    assert!(harness.step_until(60, |h| !collected(h)));
+    harness.step(120);
+    assert!(!collected(&harness));
+}
+
+#[test]
+fn reverse_contact_during_cooldown_fires_once_it_runs_out() {
+    let mut scene = Scene::new();
+    ground(&mut scene, 20.0);
+    player(&mut scene, -1.5);
+    let mut slow_wall = ReverseDirection::default();
+    slow_wall.cooldown = 3.0;
+    block(&mut scene, Vector3::new(-3.0, 0.5, 0.0), Vector3::new(0.25, 1.0, 1.0), false, Script::new(slow_wall));
+    block(&mut scene, Vector3::new(0.0, 0.5, 0.0), Vector3::new(0.25, 1.0, 1.0), false, Script::new(ReverseDirection::default()));
+
+    let mut harness = Harness::new(scene);
+    assert!(harness.step_until(300, |h| h.player().direction() == &MoveDirection::Left));
+    assert!(harness.step_until(300, |h| h.player().direction() == &MoveDirection::Right));
+    // Back at the first wall before its cooldown is over: it still turns the player once it is.
+    assert!(harness.step_until(600, |h| h.player().direction() == &MoveDirection::Left));
+}
@@ -0,0 +1,88 @@
+use battered_battery_bundles::difficulty::Difficulty;
+use battered_battery_bundles::leaderboard::{
+    GameMode, LeaderboardEntry, LeaderboardFilter, LeaderboardStore, MemoryLeaderboard, TABLE_SIZE,
+};
+
+fn entry(name: &str, level: &str, difficulty: Difficulty, score: u32, time: f32) -> LeaderboardEntry {
+    LeaderboardEntry {
+        name: name.to_string(),
+        level: level.to_string(),
+        mode: GameMode::Standard,
+        difficulty,
+        score,
+        time,
+        deliveries: 3,
+        date: "2024-03-01".to_string(),
+    }
+}
+
+#[test]
+fn entries_are_ranked_by_score_then_time() {
+    let mut board = MemoryLeaderboard::default();
+    assert_eq!(board.submit(entry("a", "scene", Difficulty::Normal, 1000, 50.0)), Some(0));
+    assert_eq!(board.submit(entry("b", "scene", Difficulty::Normal, 2000, 70.0)), Some(0));
+    assert_eq!(board.submit(entry("c", "scene", Difficulty::Normal, 1000, 40.0)), Some(1));
+
+    let names = board
+        .entries(&LeaderboardFilter::default())
+        .into_iter()
+        .map(|e| e.name)
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["b", "c", "a"]);
+}
+
+#[test]
+fn tables_are_separate_and_capped() {
+    let mut board = MemoryLeaderboard::default();
+    for i in 0..TABLE_SIZE as u32 {
+        board.submit(entry("hard", "scene", Difficulty::Hard, 100 + i, 60.0));
+    }
+    assert_eq!(board.submit(entry("low", "scene", Difficulty::Hard, 1, 60.0)), None);
+    assert_eq!(board.submit(entry("top", "scene", Difficulty::Hard, 1000, 60.0)), Some(0));
+    // A worse score still makes the table on another difficulty.
+    assert_eq!(board.submit(entry("easy", "scene", Difficulty::Easy, 1, 60.0)), Some(0));
+
+    let hard = LeaderboardFilter { difficulty: Some(Difficulty::Hard), ..Default::default() };
+    let entries = board.entries(&hard);
+    assert_eq!(entries.len(), TABLE_SIZE);
+    assert_eq!(entries[0].name, "top");
+    assert!(entries.iter().all(|e| e.score > 100));
+}
+
+#[test]
+fn filters_cycle_through_all_values() {
+    let mut board = MemoryLeaderboard::default();
+    board.submit(entry("a", "westonscene1", Difficulty::Normal, 10, 1.0));
+    board.submit(entry("b", "scene", Difficulty::Normal, 10, 1.0));
+    let levels = board.levels();
+    assert_eq!(levels, ["scene", "westonscene1"]);
+
+    let mut filter = LeaderboardFilter::default();
+    filter.next_level(&levels);
+    assert_eq!(board.entries(&filter)[0].name, "b");
+    filter.next_level(&levels);
+    assert_eq!(board.entries(&filter)[0].name, "a");
+    filter.next_level(&levels);
+    assert_eq!(filter.level, None);
+
+    filter.next_mode();
+    filter.next_mode();
+    assert_eq!(filter.mode, Some(GameMode::Daily));
+    assert!(board.entries(&filter).is_empty());
+}
+
+#[test]
+fn daily_tables_are_kept_per_date() {
+    let mut board = MemoryLeaderboard::default();
+    let daily = |name: &str, date: &str, score: u32| LeaderboardEntry {
+        mode: GameMode::Daily,
+        date: date.to_string(),
+        ..entry(name, "scene", Difficulty::Normal, score, 60.0)
+    };
+    for i in 0..TABLE_SIZE as u32 {
+        board.submit(daily("monday", "2024-03-04", 100 + i));
+    }
+    // A full table yesterday doesn't push out a weaker run today.
+    assert_eq!(board.submit(daily("tuesday", "2024-03-05", 1)), Some(0));
+    assert_eq!(board.entries(&LeaderboardFilter::default()).len(), TABLE_SIZE + 1);
+}
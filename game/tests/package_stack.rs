@@ -0,0 +1,89 @@
+use battered_battery_bundles::package_stack::{Package, PackageStack};
+
+#[test]
+fn damage_is_split_and_the_top_takes_the_remainder() {
+    let mut stack = PackageStack::new(3);
+    for _ in 0..3 {
+        stack.push(Package::new(100, ""));
+    }
+    stack.apply_damage(10);
+    let health = stack.packages().iter().map(|p| p.health).collect::<Vec<_>>();
+    assert_eq!(health, vec![97, 97, 96]);
+}
+
+#[test]
+fn delivery_only_takes_matching_packages() {
+    let mut stack = PackageStack::new(3);
+    stack.push(Package::new(100, "bakery"));
+    stack.push(Package::new(100, "docks"));
+    stack.push(Package::new(100, ""));
+    let delivered = stack.deliver("docks");
+    assert_eq!(delivered.len(), 2);
+    assert_eq!(stack.packages()[0].destination, "bakery");
+}
+
+#[test]
+fn expired_deadlines_lose_the_package() {
+    let mut stack = PackageStack::new(2);
+    stack.push(Package::new(100, "").with_deadline(1.0));
+    stack.push(Package::new(100, ""));
+    assert_eq!(stack.tick_deadlines(0.5), 0);
+    assert_eq!(stack.tick_deadlines(0.6), 1);
+    assert_eq!(stack.len(), 1);
+}
+
+#[test]
+fn a_full_stack_refuses_more_packages() {
+    let mut stack = PackageStack::new(2);
+    assert!(stack.push(Package::new(100, "")));
+    assert!(stack.push(Package::new(100, "")));
+    assert!(stack.is_full());
+    assert!(!stack.push(Package::new(100, "")));
+    assert_eq!(stack.len(), 2);
+}
+
+#[test]
+fn small_hits_land_on_the_top_packages_first() {
+    let mut stack = PackageStack::new(3);
+    for _ in 0..3 {
+        stack.push(Package::new(100, ""));
+    }
+    stack.apply_damage(2);
+    let health = stack.packages().iter().map(|p| p.health).collect::<Vec<_>>();
+    assert_eq!(health, vec![100, 99, 99]);
+}
+
+#[test]
+fn destroyed_packages_fall_off_the_stack() {
+    let mut stack = PackageStack::new(2);
+    stack.push(Package::new(100, "bakery"));
+    stack.push(Package::new(5, "docks"));
+    stack.apply_damage(20);
+    assert_eq!(stack.remove_destroyed(), 1);
+    assert_eq!(stack.packages()[0].destination, "bakery");
+    assert_eq!(stack.packages()[0].health, 90);
+}
+
+#[test]
+fn partial_drop_off_keeps_the_order_of_the_rest() {
+    let mut stack = PackageStack::new(4);
+    stack.push(Package::new(100, "bakery"));
+    stack.push(Package::new(100, "docks"));
+    stack.push(Package::new(100, "school"));
+    stack.push(Package::new(100, "docks"));
+    assert!(stack.deliver("market").is_empty());
+    assert_eq!(stack.deliver("docks").len(), 2);
+    let left = stack.packages().iter().map(|p| p.destination.as_str()).collect::<Vec<_>>();
+    assert_eq!(left, vec!["bakery", "school"]);
+    assert_eq!(stack.take_top().unwrap().destination, "school");
+}
+
+#[test]
+fn repairs_stop_at_full_health() {
+    let mut stack = PackageStack::new(2);
+    stack.push(Package::new(95, ""));
+    stack.push(Package::new(50, ""));
+    stack.repair(10, 100);
+    let health = stack.packages().iter().map(|p| p.health).collect::<Vec<_>>();
+    assert_eq!(health, vec![100, 60]);
+}
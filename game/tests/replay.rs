@@ -0,0 +1,48 @@
+mod common;
+
+use fyrox::core::algebra::Vector3;
+use fyrox::scene::Scene;
+use battered_battery_bundles::input::{InputAction, InputRecording};
+use common::{ground, player, Harness};
+
+fn scene() -> Scene {
+    let mut scene = Scene::new();
+    ground(&mut scene, 20.0);
+    player(&mut scene, 0.0);
+    scene
+}
+
+fn player_position(harness: &Harness) -> Vector3<f32> {
+    harness.graph()[harness.player_handle()].global_position()
+}
+
+#[test]
+fn replaying_a_recording_reproduces_the_run() {
+    let mut recorded = Harness::new(scene());
+    recorded.step(30);
+    recorded.press(InputAction::JumpPressed);
+    recorded.step(20);
+    recorded.press(InputAction::JumpReleased);
+    recorded.step(40);
+    recorded.press(InputAction::JumpPressed);
+    recorded.step(60);
+    let recording = recorded.input().recording().clone();
+    assert_eq!(recording.inputs.len(), 3);
+
+    let mut replayed = Harness::new(scene());
+    replayed.replay(recording);
+    replayed.step(recorded.frame as u32);
+
+    assert_eq!(player_position(&replayed), player_position(&recorded));
+    assert_eq!(replayed.player().airtime.stats.jumps, recorded.player().airtime.stats.jumps);
+}
+
+#[test]
+fn recordings_survive_a_round_trip_through_ron() {
+    let mut harness = Harness::new(scene());
+    harness.press(InputAction::DropThrough);
+    harness.step(2);
+    let recording = harness.input().recording().clone();
+    let text = ron::to_string(&recording).unwrap();
+    assert_eq!(ron::from_str::<InputRecording>(&text).unwrap(), recording);
+}
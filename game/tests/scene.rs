@@ -0,0 +1,23 @@
+mod common;
+
+use common::Harness;
+
+#[test]
+fn the_default_scene_loads_and_steps() {
+    let mut harness = Harness::load("data/scene.rgs");
+    let nodes = harness.graph().node_count();
+    assert!(nodes > 1);
+    harness.step(60);
+    assert_eq!(harness.frame, 60);
+    assert_eq!(harness.graph().node_count(), nodes);
+}
+
+#[test]
+fn couriers_carry_a_stack_of_bundles() {
+    for path in ["data/westonscene1.rgs", "data/Prefabs/Player.rgs"] {
+        let harness = Harness::load(path);
+        assert_eq!(harness.player().carry_capacity, 3, "{}", path);
+        let bundles = harness.graph().linear_iter().filter(|n| n.name().starts_with("Package")).count();
+        assert_eq!(bundles, 3, "{}", path);
+    }
+}
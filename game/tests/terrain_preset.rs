@@ -0,0 +1,61 @@
+use fyrox::asset::Resource;
+use fyrox::core::algebra::Vector3;
+use battered_battery_bundles::terrain_effect::TerrainEffect;
+use battered_battery_bundles::terrain_preset::TerrainPreset;
+
+fn oil_spill() -> TerrainPreset {
+    let mut preset = TerrainPreset::default();
+    preset.accel_modifier = -20.0;
+    preset.max_speed_mod = -20.0;
+    preset.package_damage = 10;
+    preset.linger_time = 1.5;
+    preset.lateral_force = [1.0, 0.0, 0.0];
+    preset
+}
+
+#[test]
+fn shipped_oil_spill_preset_parses() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../", "data/Presets/OilSpill.terrain");
+    let preset = TerrainPreset::from_ron(&std::fs::read_to_string(path).unwrap()).unwrap();
+    assert_eq!(preset.accel_modifier, -20.0);
+    assert_eq!(preset.package_damage, 10);
+    assert_eq!(preset.lateral_force, [0.0; 3]);
+}
+
+#[test]
+fn saved_preset_reads_back_the_same() {
+    let preset = oil_spill();
+    let read = TerrainPreset::from_ron(&preset.to_ron().unwrap()).unwrap();
+    assert_eq!(read.accel_modifier, preset.accel_modifier);
+    assert_eq!(read.package_damage, preset.package_damage);
+    assert_eq!(read.linger_time, preset.linger_time);
+    assert_eq!(read.lateral_force, preset.lateral_force);
+}
+
+#[test]
+fn preset_values_replace_local_ones_unless_overridden() {
+    let mut effect = TerrainEffect::default();
+    effect.accel_modifier = -5.0;
+    effect.max_speed_mod = -5.0;
+    effect.package_damage = 50;
+    effect.preset = Some(Resource::new_ok(oil_spill()));
+    effect.overrides.max_speed_mod = true;
+
+    assert_eq!(effect.accel_modifier(), -20.0);
+    assert_eq!(effect.max_speed_mod(), -5.0);
+    assert_eq!(effect.package_damage(), 10);
+    assert_eq!(effect.linger_time(), 1.5);
+    assert_eq!(effect.lateral_force(), Vector3::new(1.0, 0.0, 0.0));
+}
+
+#[test]
+fn local_values_apply_without_a_loaded_preset() {
+    let mut effect = TerrainEffect::default();
+    effect.accel_modifier = -5.0;
+    effect.package_damage = 50;
+    assert_eq!(effect.accel_modifier(), -5.0);
+
+    effect.preset = Some(Resource::new_pending("data/Presets/Missing.terrain".into()));
+    assert_eq!(effect.accel_modifier(), -5.0);
+    assert_eq!(effect.package_damage(), 50);
+}
@@ -0,0 +1,78 @@
+use fyrox::core::algebra::Vector3;
+use fyrox::core::pool::Handle;
+use fyrox::scene::node::Node;
+use battered_battery_bundles::terrain_effect::{TerrainContact, TerrainModifiers, TerrainStack};
+
+fn zone(index: u32, accel: f32, linger_time: f32) -> TerrainContact {
+    TerrainContact {
+        source: Handle::<Node>::new(index, 1),
+        modifiers: TerrainModifiers {
+            accel,
+            max_speed: 0.0,
+            lateral_force: Vector3::default(),
+        },
+        linger_time,
+    }
+}
+
+#[test]
+fn overlapping_zones_add_up() {
+    let mut stack = TerrainStack::default();
+    let combined = stack.update(vec![zone(1, -1.0, 0.0), zone(2, -2.0, 0.0)], 0.1);
+    assert_eq!(combined.accel, -3.0);
+}
+
+#[test]
+fn leaving_a_zone_lingers_then_expires() {
+    let mut stack = TerrainStack::default();
+    stack.update(vec![zone(1, -1.0, 0.5)], 0.1);
+    assert_eq!(stack.update(vec![], 0.1).accel, -1.0);
+    assert_eq!(stack.update(vec![], 0.3).accel, -1.0);
+    assert_eq!(stack.update(vec![], 0.3).accel, 0.0);
+}
+
+#[test]
+fn leaving_again_refreshes_instead_of_stacking() {
+    let mut stack = TerrainStack::default();
+    stack.update(vec![zone(1, -1.0, 0.5)], 0.1);
+    stack.update(vec![], 0.1);
+    stack.update(vec![zone(1, -1.0, 0.5)], 0.1);
+    assert_eq!(stack.combined().accel, -1.0);
+    stack.update(vec![], 0.1);
+    assert_eq!(stack.lingering().len(), 1);
+    assert_eq!(stack.combined().accel, -1.0);
+}
+
+#[test]
+fn a_zone_without_linger_time_stops_on_exit() {
+    let mut stack = TerrainStack::default();
+    stack.update(vec![zone(1, -1.0, 0.0)], 0.1);
+    assert_eq!(stack.update(vec![], 0.1).accel, 0.0);
+    assert!(stack.lingering().is_empty());
+}
+
+#[test]
+fn lingering_effects_from_different_zones_add_up() {
+    let mut stack = TerrainStack::default();
+    stack.update(vec![zone(1, -1.0, 1.0), zone(2, -2.0, 1.0)], 0.1);
+    assert_eq!(stack.update(vec![], 0.1).accel, -3.0);
+    assert_eq!(stack.lingering().len(), 2);
+}
+
+#[test]
+fn lingering_is_suppressed_while_back_inside_its_zone() {
+    let mut stack = TerrainStack::default();
+    stack.update(vec![zone(1, -1.0, 1.0)], 0.1);
+    stack.update(vec![], 0.1);
+    assert_eq!(stack.update(vec![zone(1, -1.0, 1.0), zone(2, -2.0, 0.0)], 0.1).accel, -3.0);
+}
+
+#[test]
+fn lateral_forces_add_up() {
+    let mut wind = zone(1, 0.0, 0.0);
+    wind.modifiers.lateral_force = Vector3::new(1.0, 0.0, 0.0);
+    let mut conveyor = zone(2, 0.0, 0.0);
+    conveyor.modifiers.lateral_force = Vector3::new(-3.0, 0.0, 0.0);
+    let mut stack = TerrainStack::default();
+    assert_eq!(stack.update(vec![wind, conveyor], 0.1).lateral_force, Vector3::new(-2.0, 0.0, 0.0));
+}
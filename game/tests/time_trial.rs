@@ -0,0 +1,48 @@
+use battered_battery_bundles::time_trial::{LevelInfo, LevelRecord, LevelTimer, Medal, RunResult};
+
+fn level() -> LevelInfo {
+    LevelInfo {
+        bronze_time: 90.0,
+        silver_time: 60.0,
+        gold_time: 45.0,
+        damage_penalty: 0.1,
+    }
+}
+
+#[test]
+fn medals_follow_par_times() {
+    let level = level();
+    assert_eq!(level.medal_for(40.0), Some(Medal::Gold));
+    assert_eq!(level.medal_for(45.0), Some(Medal::Gold));
+    assert_eq!(level.medal_for(59.0), Some(Medal::Silver));
+    assert_eq!(level.medal_for(80.0), Some(Medal::Bronze));
+    assert_eq!(level.medal_for(120.0), None);
+}
+
+#[test]
+fn damaged_packages_cost_time() {
+    let result = RunResult::new(44.0, 2, 180, 200, &level());
+    assert!((result.penalty - 2.0).abs() < 1e-4);
+    assert_eq!(result.medal, Some(Medal::Silver));
+}
+
+#[test]
+fn records_keep_the_best_time_and_medal() {
+    let mut record = LevelRecord::default();
+    assert!(record.submit(&RunResult::new(50.0, 1, 100, 100, &level())));
+    assert!(!record.submit(&RunResult::new(85.0, 1, 100, 100, &level())));
+    assert_eq!(record.best_time, Some(50.0));
+    assert_eq!(record.medal, Some(Medal::Silver));
+}
+
+#[test]
+fn timer_waits_for_the_first_movement() {
+    let mut timer = LevelTimer::default();
+    timer.update(1.0, false);
+    assert_eq!(timer.time(), 0.0);
+    timer.update(1.0, true);
+    timer.update(1.0, false);
+    timer.stop();
+    timer.update(1.0, true);
+    assert_eq!(timer.time(), 2.0);
+}
@@ -0,0 +1,36 @@
+mod common;
+
+use fyrox::scene::Scene;
+use battered_battery_bundles::tuning::GameplayTuning;
+use common::{ground, player, Harness};
+
+#[test]
+fn shipped_tuning_file_parses() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../", "data/gameplay.tuning");
+    let tuning = GameplayTuning::from_ron(&std::fs::read_to_string(path).unwrap()).unwrap();
+    assert_eq!(tuning.fall_multiplier, Some(2.0));
+    assert_eq!(tuning.max_speed, None);
+}
+
+#[test]
+fn a_removed_key_reverts_to_the_prefab_value() {
+    let mut scene = Scene::new();
+    ground(&mut scene, 20.0);
+    player(&mut scene, 0.0);
+
+    let mut harness = Harness::new(scene);
+    harness.step(60);
+    let moved = harness.graph()[harness.player_handle()].global_position().x;
+    assert!(moved < -0.5);
+
+    // The harness prefab has no top speed of its own, so dropping max_speed stops the courier.
+    let mut tuning = GameplayTuning::default();
+    tuning.accel_force = Some(10.0);
+    tuning.jump_force = Some(5.0);
+    harness.player_mut().apply_tuning(&tuning);
+    harness.step(30);
+    let stopped = harness.graph()[harness.player_handle()].global_position().x;
+    harness.step(60);
+    let x = harness.graph()[harness.player_handle()].global_position().x;
+    assert!((x - stopped).abs() < 0.05);
+}